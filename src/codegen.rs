@@ -0,0 +1,618 @@
+use std::collections::HashMap;
+
+use super::component::{Component, Prec};
+use super::equation::Equation;
+use super::operators::{Operator, Operator::*};
+use super::utils::create_binary;
+
+/// The languages functions can be generated in, besides rust
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+  /// C99 with double precision and the functions of math.h
+  C,
+  /// GLSL 1.30 or later with single precision
+  Glsl,
+  /// WGSL with single precision
+  Wgsl,
+}
+
+// Precedence of the generated expressions, values are parenthesized when they bind less strongly than their place
+const ADDITIVE: u8 = 1;
+const MULTIPLICATIVE: u8 = 2;
+const NEGATIVE: u8 = 3;
+const ATOM: u8 = 4;
+
+struct Expression {
+  text: String,
+  prec: u8,
+}
+
+impl Expression {
+  fn new(text: String, prec: u8) -> Expression {
+    Expression { text, prec }
+  }
+
+  // Text in parentheses if the expression binds less strongly than prec
+  fn at(&self, prec: u8) -> String {
+    if self.prec < prec {
+      format!("({})", self.text)
+    } else {
+      self.text.clone()
+    }
+  }
+}
+
+fn float(f: Prec) -> Expression {
+  if f.is_nan() {
+    Expression::new(String::from("f64::NAN"), ATOM)
+  } else if f.is_infinite() {
+    let name = if f < 0.0 { "NEG_INFINITY" } else { "INFINITY" };
+    Expression::new(format!("f64::{}", name), ATOM)
+  } else {
+    // Debug always writes a decimal point or exponent, so the literal is a float
+    let prec = if f.is_sign_negative() { NEGATIVE } else { ATOM };
+    Expression::new(format!("{:?}", f), prec)
+  }
+}
+
+// Translates a component to a rust expression on f64 values, which computes the same as Operator::apply
+// Returns None if the component contains other variables or invalid parts
+fn rust(component: &Component, vars: &[&str]) -> Option<Expression> {
+  let (operator, values) = match component {
+    Component::Number(f) => return Some(float(*f)),
+    Component::Variable(c) if vars.contains(&c.as_str()) => {
+      return Some(Expression::new(c.to_string(), ATOM))
+    }
+    Component::Function { operator, values } => (operator, values),
+    _ => return None,
+  };
+
+  // The bound variable of a sum or product is a closure parameter in the body
+  if let Some(k) = component.bound_variable() {
+    let mut inner = vars.to_vec();
+    inner.push(k);
+    let (body, a, b) = (
+      rust(&values[0], &inner)?,
+      rust(&values[2], vars)?,
+      rust(&values[3], vars)?,
+    );
+    return Some(Expression::new(
+      format!(
+        "std::iter::successors(Some({}), |{k}| Some({k} + 1.0)).take_while(|&{k}| {k} <= {}).map(|{k}| {}).{}::<f64>()",
+        a.text,
+        b.text,
+        body.text,
+        if operator.compare(&Sum) { "sum" } else { "product" },
+        k = k
+      ),
+      ATOM,
+    ));
+  }
+
+  let values = values
+    .iter()
+    .map(|value| rust(value, vars))
+    .collect::<Option<Vec<_>>>()?;
+  let infix = |symbol: &str, prec: u8| {
+    // The operators are left associative, so the right value also needs parentheses at the same precedence
+    let text = format!(
+      "{} {} {}",
+      values[0].at(prec),
+      symbol,
+      values[1].at(prec + 1)
+    );
+    Some(Expression::new(text, prec))
+  };
+  let method = |name: &str| {
+    Some(Expression::new(
+      format!("{}.{}()", values[0].at(ATOM), name),
+      ATOM,
+    ))
+  };
+  let reciprocal = |name: &str| {
+    let text = format!("1.0 / {}.{}()", values[0].at(ATOM), name);
+    Some(Expression::new(text, MULTIPLICATIVE))
+  };
+
+  match (operator, values.len()) {
+    (Add, 2) => infix("+", ADDITIVE),
+    (Subtract, 2) => infix("-", ADDITIVE),
+    (Multiply, 2) => infix("*", MULTIPLICATIVE),
+    (Divide, 2) => infix("/", MULTIPLICATIVE),
+    (Modulo, 2) => infix("%", MULTIPLICATIVE),
+    (Exponent, 2) | (Pow, 2) => Some(Expression::new(
+      format!("{}.powf({})", values[0].at(ATOM), values[1].text),
+      ATOM,
+    )),
+    (Log, 2) => Some(Expression::new(
+      format!("{}.log({})", values[0].at(ATOM), values[1].text),
+      ATOM,
+    )),
+    (Root, 2) => Some(Expression::new(
+      format!(
+        "{}.powf(1.0 / {})",
+        values[0].at(ATOM),
+        values[1].at(NEGATIVE)
+      ),
+      ATOM,
+    )),
+    (Sin, 1) => method("sin"),
+    (Cos, 1) => method("cos"),
+    (Tan, 1) => method("tan"),
+    (Sec, 1) => reciprocal("cos"),
+    (Csc, 1) => reciprocal("sin"),
+    (Cot, 1) => reciprocal("tan"),
+    (Abs, 1) => method("abs"),
+    (Floor, 1) => method("floor"),
+    (Round, 1) => method("round"),
+    (Ceil, 1) => method("ceil"),
+    (Exp, 1) => method("exp"),
+    (Ln, 1) => method("ln"),
+    (Sqrt, 1) => method("sqrt"),
+    _ => None,
+  }
+}
+
+/// Generates a rust function with an f64 parameter for every variable in vars, which evaluates the component
+/// Returns None if the component contains other variables or invalid parts
+pub fn rust_fn(component: &Component, name: &str, vars: &[&str]) -> Option<String> {
+  let parameters: Vec<_> = vars.iter().map(|var| format!("{}: f64", var)).collect();
+  Some(format!(
+    "fn {}({}) -> f64 {{\n    {}\n}}\n",
+    name,
+    parameters.join(", "),
+    rust(component, vars)?.text
+  ))
+}
+
+/// Generates rust source code with a public function for every formula and for each of its partial derivatives,
+/// meant to be written to a file by a build script and included with include!
+/// The formulas are given with their function name, the derivative to x of a function f is called f_dx
+/// Returns None if a formula contains other variables or invalid parts
+///
+/// # Examples
+/// ```
+/// let source = calculi::rust_functions(&[("area", "pi * r ^ 2")], &["pi", "r"]).unwrap();
+///
+/// assert!(source.contains("pub fn area(pi: f64, r: f64) -> f64 {\n    pi * r.powf(2.0)\n}"));
+/// assert!(source.contains("pub fn area_dr(pi: f64, r: f64) -> f64 {\n    2.0 * (pi * r)\n}"));
+/// ```
+pub fn rust_functions(formulas: &[(&str, &str)], vars: &[&str]) -> Option<String> {
+  let mut source = String::from("// Generated by calculi, do not edit\n");
+  for (name, text) in formulas {
+    let eq = Equation::new(*text);
+    let derivatives = eq.gradient(vars);
+    let functions = std::iter::once((name.to_string(), eq.text.clone(), &eq)).chain(
+      vars.iter().zip(&derivatives).map(|(var, derivative)| {
+        let description = format!("d / d{} of {}", var, eq.text);
+        (format!("{}_d{}", name, var), description, derivative)
+      }),
+    );
+
+    for (name, description, eq) in functions {
+      source.push_str(&format!(
+        "\n/// {}\n#[allow(unused_variables)]\npub {}",
+        description,
+        rust_fn(&eq.expression, &name, vars)?
+      ));
+    }
+  }
+  Some(source)
+}
+
+// Largest integer exponent which is written out as multiplications in shaders
+// pow is undefined for negative bases there, while x ^ 2 is common
+const MULTIPLIED_POWERS: Prec = 4.0;
+
+// Rewrites integer powers as multiplications, x ^ 3 becomes x * x * x and x ^ -2 becomes 1 / (x * x)
+fn multiply_powers(component: &Component) -> Component {
+  match component {
+    Component::Function { operator, values } => {
+      let values: Vec<_> = values.iter().map(multiply_powers).collect();
+      match (operator, values.as_slice()) {
+        (Exponent, [base, Component::Number(n)]) | (Pow, [base, Component::Number(n)])
+          if n.fract() == 0.0 && *n != 0.0 && n.abs() <= MULTIPLIED_POWERS =>
+        {
+          let mut product = base.clone();
+          for _ in 1..n.abs() as usize {
+            product = create_binary(Multiply, product, base.clone());
+          }
+          if *n < 0.0 {
+            create_binary(Divide, Component::Number(1.0), product)
+          } else {
+            product
+          }
+        }
+        _ => Component::Function {
+          operator: operator.clone(),
+          values,
+        },
+      }
+    }
+    _ => component.clone(),
+  }
+}
+
+// Collects the names of all variables, including the bound variables of sums and products
+fn names(component: &Component, names: &mut Vec<String>) {
+  match component {
+    Component::Variable(c) => names.push(c.to_string()),
+    Component::Function { values, .. } => values.iter().for_each(|value| self::names(value, names)),
+    _ => (),
+  }
+}
+
+// Generates the statements of a function, values which occur more than once are computed once in a temporary
+struct Generator {
+  language: Language,
+  vars: Vec<String>,
+  // The amount of times every sub component occurs, by their text
+  counts: HashMap<String, usize>,
+  // The temporaries which are in scope, by the text of the component they hold
+  temporaries: HashMap<String, Temporary>,
+  // Names which can not be used for temporaries
+  reserved: Vec<String>,
+  lines: Vec<String>,
+  // The loops of the sums and products which are being generated, with their first line and bound variable
+  loops: Vec<(usize, String)>,
+  next: usize,
+}
+
+#[derive(Clone)]
+struct Temporary {
+  name: String,
+  // The amount of loops around the declaration
+  level: usize,
+  variables: Vec<String>,
+}
+
+impl Generator {
+  // Counts the sub components, a repeated component is only counted once, not its values
+  fn count(&mut self, component: &Component) {
+    let count = self.counts.entry(component.to_string()).or_insert(0);
+    *count += 1;
+    if let (1, Component::Function { values, .. }) = (*count, component) {
+      values.iter().for_each(|value| self.count(value));
+    }
+  }
+
+  fn float_type(&self) -> &'static str {
+    match self.language {
+      Language::C => "double",
+      Language::Glsl => "float",
+      Language::Wgsl => "f32",
+    }
+  }
+
+  fn push(&mut self, line: String) {
+    let indent = "    ".repeat(self.loops.len() + 1);
+    self.lines.push(format!("{}{}", indent, line));
+  }
+
+  // Declares a new temporary with the given value and returns its name
+  fn declare(&mut self, value: &str, mutable: bool) -> String {
+    let (name, line) = self.declaration(value, mutable);
+    self.push(line);
+    name
+  }
+
+  // Declares a temporary outside of the loops whose bound variables the value does not depend on
+  fn hoist(&mut self, value: &str, component: &Component) -> Temporary {
+    let variables = component.variables();
+    let level = self
+      .loops
+      .iter()
+      .rposition(|(_, k)| variables.contains(k))
+      .map_or(0, |i| i + 1);
+    if level == self.loops.len() {
+      let name = self.declare(value, false);
+      return Temporary {
+        name,
+        level,
+        variables,
+      };
+    }
+
+    let (name, line) = self.declaration(value, false);
+    let indent = "    ".repeat(level + 1);
+    self
+      .lines
+      .insert(self.loops[level].0, format!("{}{}", indent, line));
+    for (start, _) in &mut self.loops[level..] {
+      *start += 1;
+    }
+    Temporary {
+      name,
+      level,
+      variables,
+    }
+  }
+
+  // A new name for a temporary and the line declaring it
+  fn declaration(&mut self, value: &str, mutable: bool) -> (String, String) {
+    let mut name = format!("t{}", self.next);
+    while self.reserved.contains(&name) {
+      self.next += 1;
+      name = format!("t{}", self.next);
+    }
+    self.next += 1;
+
+    let line = match (self.language, mutable) {
+      (Language::C, false) => format!("const double {} = {};", name, value),
+      (Language::Wgsl, false) => format!("let {} = {};", name, value),
+      (Language::Wgsl, true) => format!("var {}: f32 = {};", name, value),
+      _ => format!("{} {} = {};", self.float_type(), name, value),
+    };
+    (name, line)
+  }
+
+  // Expression which can be used more than once without computing it again
+  // Names and numbers are kept, other expressions are stored in a temporary
+  fn reusable(&mut self, expression: Expression, component: &Component) -> Expression {
+    if expression.text.contains([' ', '(']) {
+      Expression::new(self.hoist(&expression.text, component).name, ATOM)
+    } else {
+      expression
+    }
+  }
+
+  fn float(&self, f: Prec) -> Expression {
+    // NaN and infinity have no literal, so they are made from their bits in shaders
+    let bits = |bits: &str| match self.language {
+      Language::Glsl => format!("uintBitsToFloat({})", bits),
+      _ => format!("bitcast<f32>({})", bits),
+    };
+    match self.language {
+      Language::C if f.is_nan() => Expression::new(String::from("NAN"), ATOM),
+      Language::C if f.is_infinite() => {
+        let text = if f < 0.0 { "-INFINITY" } else { "INFINITY" };
+        Expression::new(String::from(text), NEGATIVE)
+      }
+      _ if f.is_nan() => Expression::new(bits("0x7fc00000u"), ATOM),
+      _ if f.is_infinite() => Expression::new(
+        bits(if f < 0.0 {
+          "0xff800000u"
+        } else {
+          "0x7f800000u"
+        }),
+        ATOM,
+      ),
+      _ => float(f),
+    }
+  }
+
+  fn expression(&mut self, component: &Component) -> Option<Expression> {
+    let key = component.to_string();
+    if let Some(temporary) = self.temporaries.get(&key) {
+      return Some(Expression::new(temporary.name.clone(), ATOM));
+    }
+
+    let expression = match component {
+      Component::Number(f) => return Some(self.float(*f)),
+      Component::Variable(c) if self.vars.contains(c) => {
+        return Some(Expression::new(c.to_string(), ATOM))
+      }
+      Component::Function { operator, values } if component.bound_variable().is_some() => {
+        return self.sum(component, key)
+      }
+      Component::Function { operator, values } => self.function(operator, values)?,
+      _ => return None,
+    };
+
+    // Repeated functions are stored in a temporary when they are first computed
+    if self.counts.get(&key).copied().unwrap_or(0) > 1 {
+      let temporary = self.hoist(&expression.text, component);
+      let name = temporary.name.clone();
+      self.temporaries.insert(key, temporary);
+      return Some(Expression::new(name, ATOM));
+    }
+    Some(expression)
+  }
+
+  // A sum or product is a loop adding or multiplying the terms into a temporary
+  fn sum(&mut self, component: &Component, key: String) -> Option<Expression> {
+    let (sum, values, k) = match component {
+      Component::Function { operator, values } => {
+        (operator.compare(&Sum), values, component.bound_variable()?)
+      }
+      _ => return None,
+    };
+    let a = self.expression(&values[2])?;
+    // The upper bound is compared in every iteration
+    let b = self.expression(&values[3])?;
+    let b = self.reusable(b, &values[3]);
+    let start = self.lines.len();
+    let result = self.declare(if sum { "0.0" } else { "1.0" }, true);
+    self.push(match self.language {
+      Language::Wgsl => format!(
+        "for (var {k}: f32 = {}; {k} <= {}; {k} += 1.0) {{",
+        a.text,
+        b.text,
+        k = k
+      ),
+      _ => format!(
+        "for ({} {k} = {}; {k} <= {}; {k} += 1.0) {{",
+        self.float_type(),
+        a.text,
+        b.text,
+        k = k
+      ),
+    });
+
+    // The bound variable hides other variables with the same name in the loop
+    let outside = self.temporaries.clone();
+    self
+      .temporaries
+      .retain(|_, temporary| !temporary.variables.iter().any(|v| v == k));
+    self.vars.push(k.to_string());
+    self.loops.push((start, k.to_string()));
+    let body = self.expression(&values[0]);
+    if let Some(body) = &body {
+      let line = format!("{} {}= {};", result, if sum { "+" } else { "*" }, body.text);
+      self.push(line);
+    }
+
+    // Temporaries computed in the loop are only in scope in the loop, unless they were hoisted out of it
+    let level = self.loops.len();
+    self.loops.pop();
+    self.vars.pop();
+    let inside = std::mem::replace(&mut self.temporaries, outside);
+    for (text, temporary) in inside {
+      if temporary.level < level {
+        self.temporaries.entry(text).or_insert(temporary);
+      }
+    }
+    self.push(String::from("}"));
+
+    let level = self.loops.len();
+    self.temporaries.insert(
+      key,
+      Temporary {
+        name: result.clone(),
+        level,
+        variables: component.variables(),
+      },
+    );
+    body.map(|_| Expression::new(result, ATOM))
+  }
+
+  fn function(&mut self, operator: &Operator, values: &[Component]) -> Option<Expression> {
+    let mut arguments = values
+      .iter()
+      .map(|value| self.expression(value))
+      .collect::<Option<Vec<_>>>()?;
+    let call = |name: &str, arguments: &[Expression]| {
+      let arguments: Vec<_> = arguments.iter().map(|x| x.text.as_str()).collect();
+      Some(Expression::new(
+        format!("{}({})", name, arguments.join(", ")),
+        ATOM,
+      ))
+    };
+    let infix = |symbol: &str, prec: u8, arguments: &[Expression]| {
+      // The operators are left associative, so the right value also needs parentheses at the same precedence
+      let text = format!(
+        "{} {} {}",
+        arguments[0].at(prec),
+        symbol,
+        arguments[1].at(prec + 1)
+      );
+      Some(Expression::new(text, prec))
+    };
+    let reciprocal = |name: &str, arguments: &[Expression]| {
+      let text = format!("1.0 / {}({})", name, arguments[0].text);
+      Some(Expression::new(text, MULTIPLICATIVE))
+    };
+    let shader = self.language != Language::C;
+
+    match (operator, arguments.len()) {
+      (Add, 2) => infix("+", ADDITIVE, &arguments),
+      (Subtract, 2) => infix("-", ADDITIVE, &arguments),
+      (Multiply, 2) => infix("*", MULTIPLICATIVE, &arguments),
+      (Divide, 2) => infix("/", MULTIPLICATIVE, &arguments),
+      (Modulo, 2) => match self.language {
+        Language::C => call("fmod", &arguments),
+        Language::Wgsl => infix("%", MULTIPLICATIVE, &arguments),
+        // mod in glsl rounds the quotient down instead of towards zero
+        Language::Glsl => {
+          let b = arguments.pop()?;
+          let a = arguments.pop()?;
+          let (a, b) = (self.reusable(a, &values[0]), self.reusable(b, &values[1]));
+          let text = format!(
+            "{} - {} * trunc({} / {})",
+            a.text,
+            b.at(NEGATIVE),
+            a.at(NEGATIVE),
+            b.at(NEGATIVE)
+          );
+          Some(Expression::new(text, ADDITIVE))
+        }
+      },
+      (Exponent, 2) | (Pow, 2) => call("pow", &arguments),
+      // log(n, base) equals ln(n) / ln(base)
+      (Log, 2) => Some(Expression::new(
+        format!("log({}) / log({})", arguments[0].text, arguments[1].text),
+        MULTIPLICATIVE,
+      )),
+      // root(n, k) equals n ^ (1 / k)
+      (Root, 2) => Some(Expression::new(
+        format!(
+          "pow({}, 1.0 / {})",
+          arguments[0].text,
+          arguments[1].at(NEGATIVE)
+        ),
+        ATOM,
+      )),
+      (Sin, 1) => call("sin", &arguments),
+      (Cos, 1) => call("cos", &arguments),
+      (Tan, 1) => call("tan", &arguments),
+      (Sec, 1) => reciprocal("cos", &arguments),
+      (Csc, 1) => reciprocal("sin", &arguments),
+      (Cot, 1) => reciprocal("tan", &arguments),
+      (Abs, 1) => call(if shader { "abs" } else { "fabs" }, &arguments),
+      (Floor, 1) => call("floor", &arguments),
+      // round in shaders may round halves to even, rust rounds them away from zero
+      (Round, 1) if shader => {
+        let a = self.reusable(arguments.pop()?, &values[0]);
+        let text = format!("sign({}) * floor(abs({}) + 0.5)", a.text, a.text);
+        Some(Expression::new(text, MULTIPLICATIVE))
+      }
+      (Round, 1) => call("round", &arguments),
+      (Ceil, 1) => call("ceil", &arguments),
+      (Exp, 1) => call("exp", &arguments),
+      (Ln, 1) => call("log", &arguments),
+      (Sqrt, 1) => call("sqrt", &arguments),
+      _ => None,
+    }
+  }
+}
+
+/// Generates a function in the given language with a parameter for every variable in vars, which evaluates the component
+/// Values which occur more than once are computed once and stored in temporaries
+/// Returns None if the component contains other variables or invalid parts
+pub fn to_code(
+  component: &Component,
+  language: Language,
+  name: &str,
+  vars: &[&str],
+) -> Option<String> {
+  let component = match language {
+    Language::C => component.clone(),
+    _ => multiply_powers(component),
+  };
+  let mut reserved = vec![];
+  names(&component, &mut reserved);
+
+  let mut generator = Generator {
+    language,
+    vars: vars.iter().map(|var| var.to_string()).collect(),
+    counts: HashMap::new(),
+    temporaries: HashMap::new(),
+    reserved,
+    lines: vec![],
+    loops: vec![],
+    next: 0,
+  };
+  generator.count(&component);
+  let result = generator.expression(&component)?;
+  generator.push(format!("return {};", result.text));
+
+  let float_type = generator.float_type();
+  let signature = match language {
+    Language::Wgsl => {
+      let parameters: Vec<_> = vars.iter().map(|var| format!("{}: f32", var)).collect();
+      format!("fn {}({}) -> f32 {{", name, parameters.join(", "))
+    }
+    _ => {
+      let parameters: Vec<_> = vars
+        .iter()
+        .map(|var| format!("{} {}", float_type, var))
+        .collect();
+      format!("{} {}({}) {{", float_type, name, parameters.join(", "))
+    }
+  };
+  Some(format!(
+    "{}\n{}\n}}\n",
+    signature,
+    generator.lines.join("\n")
+  ))
+}
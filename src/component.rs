@@ -1,12 +1,41 @@
 use std::fmt;
 
+use super::dot;
+use super::latex;
+use super::mathml;
+use super::mathml::MathML;
 use super::operators;
 use super::operators::Operator;
+use super::pretty::{Charset, Pretty};
+use super::sexpr;
 
 pub type Prec = f32;
 
 /// The possible equation components
-#[derive(Debug, Clone)]
+///
+/// With the `serde` feature, components are serialized as a tree:
+/// * A number is a number, like `2.5`, except NaN and the infinities which json can't represent,
+///   they are written as `{"num":"NaN"}`, `{"num":"inf"}` and `{"num":"-inf"}`
+/// * A variable is a string with its name, like `"x"`
+/// * A function is an object with the operator and its arguments, like `{"op":"sin","args":["x"]}`,
+///   the amount of arguments must match the operator
+/// * An empty component is `null`
+///
+/// Operators are named as they are written in equations, like `"+"`, `"^"`, `"log"` or `"prod"`.
+/// To store a component as its infix string instead, use `#[serde(with = "calculi::infix")]`.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "serde")]
+/// # {
+/// let eq = calculi::Equation::new("2 * sin(x)");
+/// let json = serde_json::to_string(&eq.expression).unwrap();
+///
+/// assert_eq!(json, r#"{"op":"*","args":[2.0,{"op":"sin","args":["x"]}]}"#);
+/// assert_eq!(serde_json::from_str::<calculi::Component>(&json).unwrap(), eq.expression);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
 pub enum Component {
   Variable(String),
   Number(Prec),
@@ -19,13 +48,14 @@ pub enum Component {
 
 impl fmt::Display for Component {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    f.write_str(&self.to_text_prec(0))
+    f.write_str(&self.to_text())
   }
 }
 
 impl Component {
   // Converts component to a readable text
-  fn to_text_prec(&self, prev_prec: i8) -> String {
+  // Values only get parentheses when the parser would read them differently without
+  fn to_text(&self) -> String {
     match self {
       Component::Variable(c) => c.to_string(),
       Component::Number(f) => f.to_string(),
@@ -33,31 +63,132 @@ impl Component {
         let op_value = operator.to_string();
 
         // Standard binary operator
-        // Checks if parenthesis surround expression with operator precedence
-        if op_value.len() == 1 {
-          let prec = operators::get_precedence(Some(&op_value.chars().next().unwrap()));
+        if op_value.len() == 1 && values.len() == 2 {
+          let prec = operators::get_precedence(op_value.chars().next().as_ref());
+          // ^ is right associative, so (a ^ b) ^ c needs parentheses and a ^ (b ^ c) does not
+          // The other operators are left associative, so a - (b - c) needs parentheses
+          let right_associative = operator.compare(&Operator::Exponent);
           format!(
-            "{}{} {} {}{}",
-            if prec < prev_prec { "(" } else { "" },
-            values[0].to_text_prec(prec),
+            "{} {} {}",
+            values[0].operand(prec, right_associative),
             op_value,
-            values[1].to_text_prec(prec),
-            if prec < prev_prec { ")" } else { "" }
+            values[1].operand(prec, !right_associative)
           )
 
         // Function operator function
         } else {
-          let mut parameters = values[0].to_string();
-          for x in values.iter().skip(1) {
-            parameters = format!("{}, {}", parameters, x.to_string());
-          }
-          format!("{}({})", op_value, parameters)
+          let parameters: Vec<_> = values.iter().map(|x| x.to_string()).collect();
+          format!("{}({})", op_value, parameters.join(", "))
         }
       }
       _ => String::from(""),
     }
   }
 
+  // Text of a value of a binary operator, in parentheses if it binds less strongly than the operator
+  fn operand(&self, prec: i8, parenthesize_equal: bool) -> String {
+    let own = match self {
+      Component::Function { operator, values } if values.len() == 2 => {
+        let op_value = operator.to_string();
+        if op_value.len() == 1 {
+          operators::get_precedence(op_value.chars().next().as_ref())
+        } else {
+          i8::MAX
+        }
+      }
+      // The sign of a number is applied after exponents, so -2 ^ 2 is -4
+      Component::Number(f) if f.is_sign_negative() => operators::get_precedence(Some(&'^')),
+      _ => i8::MAX,
+    };
+
+    if own < prec || (own == prec && parenthesize_equal) {
+      format!("({})", self.to_text())
+    } else {
+      self.to_text()
+    }
+  }
+
+  // Collects the names of all variables in the component, sorted and without duplicates
+  pub(crate) fn variables(&self) -> Vec<String> {
+    let mut vars = vec![];
+    self.collect_variables(&mut vars);
+    vars.sort();
+    vars.dedup();
+    vars
+  }
+
+  fn collect_variables(&self, vars: &mut Vec<String>) {
+    match self {
+      Component::Variable(c) => vars.push(c.to_string()),
+      Component::Function { values, .. } => match self.bound_variable() {
+        // The bound variable of a sum or product is only free in the bounds
+        Some(bound) => {
+          let mut inner = vec![];
+          values[0].collect_variables(&mut inner);
+          vars.extend(inner.into_iter().filter(|v| v != bound));
+          for value in &values[2..] {
+            value.collect_variables(vars);
+          }
+        }
+        None => {
+          for value in values {
+            value.collect_variables(vars);
+          }
+        }
+      },
+      _ => (),
+    }
+  }
+
+  // The variable which is bound by a sum or product, like k in sum(k ^ 2, k, 1, n)
+  pub(crate) fn bound_variable(&self) -> Option<&str> {
+    match self {
+      Component::Function {
+        operator: Operator::Sum,
+        values,
+      }
+      | Component::Function {
+        operator: Operator::Product,
+        values,
+      } if values.len() == 4 => match &values[1] {
+        Component::Variable(k) => Some(k),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+
+  // Replaces every occurrence of a sub component with another component
+  pub(crate) fn substitute(&self, from: &Component, to: &Component) -> Component {
+    if self.to_string() == from.to_string() {
+      return to.clone();
+    }
+
+    match self {
+      Component::Function { operator, values } => {
+        // Components containing the bound variable of a sum or product are only replaced in the bounds
+        let shadowed = self
+          .bound_variable()
+          .is_some_and(|k| from.variables().iter().any(|v| v == k));
+        Component::Function {
+          operator: operator.clone(),
+          values: values
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+              if i < 2 && shadowed {
+                x.clone()
+              } else {
+                x.substitute(from, to)
+              }
+            })
+            .collect(),
+        }
+      }
+      _ => self.clone(),
+    }
+  }
+
   /// Attempts to convert the component to a float if it is a number
   /// Returns None if component is not a number
   ///
@@ -73,4 +204,105 @@ impl Component {
       _ => None,
     }
   }
+
+  /// Converts the component to LaTeX, fractions, powers and roots are typeset
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("root(y, 3) / (x + 1) + sin(x) ^ 2");
+  ///
+  /// assert_eq!(eq.expression.to_latex(), "\\frac{\\sqrt[3]{y}}{x + 1} + \\sin\\left(x\\right)^{2}");
+  /// ```
+  pub fn to_latex(&self) -> String {
+    latex::to_latex(self)
+  }
+
+  /// Converts the component to presentation or content MathML, wrapped in a math element
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::{Equation, MathML};
+  ///
+  /// let eq = Equation::new("sqrt(x) / 2");
+  ///
+  /// assert_eq!(
+  ///   eq.expression.to_mathml(MathML::Presentation),
+  ///   r#"<math xmlns="http://www.w3.org/1998/Math/MathML"><mfrac><msqrt><mi>x</mi></msqrt><mn>2</mn></mfrac></math>"#
+  /// );
+  /// assert_eq!(
+  ///   eq.expression.to_mathml(MathML::Content),
+  ///   r#"<math xmlns="http://www.w3.org/1998/Math/MathML"><apply><divide/><apply><root/><ci>x</ci></apply><cn>2</cn></apply></math>"#
+  /// );
+  /// ```
+  pub fn to_mathml(&self, markup: MathML) -> String {
+    mathml::to_mathml(self, markup)
+  }
+
+  /// Converts the component to a Graphviz digraph, with operators as nodes and the argument order on the edges
+  /// The nodes and edges on the paths to the highlighted variable are colored red
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("sin(x) * 2");
+  ///
+  /// assert_eq!(
+  ///   eq.expression.to_dot(Some("x")),
+  ///   "digraph {
+  ///   n0 [label=\"*\", shape=ellipse, color=red, fontcolor=red, penwidth=2];
+  ///   n1 [label=\"sin\", shape=ellipse, color=red, fontcolor=red, penwidth=2];
+  ///   n2 [label=\"x\", shape=box, color=red, fontcolor=red, penwidth=2];
+  ///   n1 -> n2 [label=\"0\", color=red, fontcolor=red, penwidth=2];
+  ///   n0 -> n1 [label=\"0\", color=red, fontcolor=red, penwidth=2];
+  ///   n3 [label=\"2\", shape=box];
+  ///   n0 -> n3 [label=\"1\"];
+  /// }"
+  /// );
+  /// ```
+  pub fn to_dot(&self, highlight: Option<&str>) -> String {
+    dot::to_dot(self, highlight)
+  }
+
+  /// Converts the component to an s-expression, every function is a list with the operator first
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("x + 2 * y ^ 3");
+  ///
+  /// assert_eq!(eq.expression.to_sexpr(), "(+ x (* 2 (^ y 3)))");
+  /// ```
+  pub fn to_sexpr(&self) -> String {
+    sexpr::to_sexpr(self)
+  }
+
+  /// Parses an s-expression like (+ x (* 2 y)), +, -, * and / take any amount of values
+  /// Returns None if the s-expression is invalid
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::Component;
+  ///
+  /// let component = Component::from_sexpr("(- (* 2 x y) (sin x) (sum (^ k 2) k 1 n))").unwrap();
+  ///
+  /// assert_eq!(component.to_string(), "2 * x * y - sin(x) - sum(k ^ 2, k, 1, n)");
+  /// assert!(Component::from_sexpr("(sin x y)").is_none());
+  /// ```
+  pub fn from_sexpr(text: &str) -> Option<Component> {
+    sexpr::from_sexpr(text)
+  }
+
+  /// Lays out the component over multiple lines, with fractions, exponents and radicals drawn in two dimensions
+  /// The returned value implements Display, like the component itself
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::{Charset, Equation};
+  ///
+  /// let eq = Equation::new("sqrt(x) / 2 + y ^ 2");
+  ///
+  /// assert_eq!(eq.expression.pretty(Charset::Unicode).to_string(), "   __\n ╲╱ x     2\n────── + y\n  2");
+  /// assert_eq!(eq.expression.pretty(Charset::Ascii).to_string(), "   __\n \\/ x     2\n------ + y\n  2");
+  /// ```
+  pub fn pretty(&self, charset: Charset) -> Pretty<'_> {
+    Pretty::new(self, charset)
+  }
 }
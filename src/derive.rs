@@ -1,15 +1,429 @@
-use super::utils::*;
-use super::component::Component;
+use std::collections::HashMap;
+
+use super::component::{Component, Prec};
 use super::operators::Operator::*;
+use super::utils::*;
+
+// Maximum amount of simplification passes between derivatives
+const MAX_PASSES: usize = 16;
+
+// Caches the partial derivatives of a component, so higher derivatives reuse lower ones
+// Mixed partials are assumed to be symmetric, the order of the variables does not matter
+pub(crate) struct Derivatives {
+  cache: HashMap<Vec<String>, Component>,
+}
+
+impl Derivatives {
+  pub(crate) fn new(component: &Component) -> Derivatives {
+    let mut cache = HashMap::new();
+    cache.insert(vec![], simplify(component));
+    Derivatives { cache }
+  }
 
-fn chain_rule(left: Component, right: &Component) -> Component {
-  create_binary(Multiply, left, derive_component(right))
+  // Derives with respect to every variable in vars, a variable may occur multiple times
+  pub(crate) fn get(&mut self, vars: &[&str]) -> Component {
+    let mut key: Vec<String> = vars.iter().map(|v| v.to_string()).collect();
+    key.sort();
+
+    // Start from the highest derivative in the cache which leads to this one
+    let mut order = (0..=key.len())
+      .rev()
+      .find(|i| self.cache.contains_key(&key[..*i]))
+      .unwrap();
+    let mut derived = self.cache[&key[..order]].clone();
+
+    while order < key.len() {
+      derived = simplify(&derive_partial(&derived, &key[order]));
+      order += 1;
+      self.cache.insert(key[..order].to_vec(), derived.clone());
+    }
+    derived
+  }
+}
+
+// Splits a product into its factors
+fn collect_factors(component: &Component, factors: &mut Vec<Component>) {
+  match component {
+    Component::Function {
+      operator: Multiply,
+      values,
+    } => {
+      collect_factors(&values[0], factors);
+      collect_factors(&values[1], factors);
+    }
+    _ => factors.push(component.clone()),
+  }
 }
 
+// Numeric coefficient of a product and the remaining factors
+fn coefficient(component: &Component) -> (Prec, Option<Component>) {
+  let mut factors = vec![];
+  collect_factors(component, &mut factors);
+
+  let mut coefficient = 1.0;
+  let mut rest: Option<Component> = None;
+  for factor in factors {
+    match factor.to_float() {
+      Some(f) => coefficient *= f,
+      None => {
+        rest = Some(match rest {
+          Some(rest) => create_binary(Multiply, rest, factor),
+          None => factor,
+        })
+      }
+    }
+  }
+  (coefficient, rest)
+}
+
+fn scale(coefficient: Prec, rest: Option<Component>) -> Component {
+  match rest {
+    Some(rest) if coefficient == 1.0 => rest,
+    Some(rest) if coefficient != 0.0 => {
+      create_binary(Multiply, Component::Number(coefficient), rest)
+    }
+    Some(_) => Component::Number(0.0),
+    None => Component::Number(coefficient),
+  }
+}
+
+// Merges the numbers in products and moves negative coefficients into sums and differences,
+// like 4 * (3 * x) to 12 * x and x - -1 * y to x + y
+fn fold(component: &Component) -> Component {
+  let (operator, values) = match component {
+    Component::Function { operator, values } => {
+      (operator, values.iter().map(fold).collect::<Vec<_>>())
+    }
+    _ => return component.clone(),
+  };
+
+  match (operator, values.as_slice()) {
+    (Multiply, [left, right]) => {
+      let (coefficient, rest) = coefficient(&create_binary(Multiply, left.clone(), right.clone()));
+      scale(coefficient, rest)
+    }
+    (Add, [left, right]) | (Subtract, [left, right]) => {
+      let (coefficient, rest) = coefficient(right);
+      match (rest, left.to_float()) {
+        // x + -a * y, becomes x - a * y
+        (Some(rest), _) if coefficient < 0.0 => create_binary(
+          if operator.compare(&Add) {
+            Subtract
+          } else {
+            Add
+          },
+          left.clone(),
+          scale(-coefficient, Some(rest)),
+        ),
+        // 0 - y, becomes -1 * y
+        (Some(rest), Some(f)) if f == 0.0 && operator.compare(&Subtract) => {
+          scale(-coefficient, Some(rest))
+        }
+        _ => create_binary(operator.clone(), left.clone(), right.clone()),
+      }
+    }
+    // (x ^ a) ^ b, becomes x ^ (a * b)
+    // Only for integer powers, (x ^ 2) ^ 0.5 is abs(x) and (x ^ 0.5) ^ 2 is not defined for negative x
+    (
+      Exponent,
+      [Component::Function {
+        operator: Exponent,
+        values: inner,
+      }, power],
+    ) => match (inner[1].to_float(), power.to_float()) {
+      (Some(a), Some(b)) if a.fract() == 0.0 && b.fract() == 0.0 => {
+        create_binary(Exponent, inner[0].clone(), Component::Number(a * b))
+      }
+      _ => create_binary(Exponent, values[0].clone(), values[1].clone()),
+    },
+    _ => Component::Function {
+      operator: operator.clone(),
+      values,
+    },
+  }
+}
+
+// A coefficient times a product of factors with integer powers, factors are kept in order of appearance
+#[derive(Clone)]
+struct Term {
+  coefficient: Prec,
+  factors: Vec<(String, Component, Prec)>,
+}
+
+impl Term {
+  fn number(coefficient: Prec) -> Term {
+    Term {
+      coefficient,
+      factors: vec![],
+    }
+  }
+
+  fn factor(component: Component, power: Prec) -> Term {
+    Term {
+      coefficient: 1.0,
+      factors: vec![(component.to_string(), component, power)],
+    }
+  }
+
+  // A sum of several terms becomes a single factor
+  // The coefficient of the first term is moved out when it divides the others, so 2 - 2 * x and 1 - x are the same factor
+  fn from_sum(mut terms: Vec<Term>) -> Term {
+    match terms.len() {
+      0 => Term::number(0.0),
+      1 => terms.remove(0),
+      _ => {
+        let first = terms[0].coefficient;
+        let content = if terms
+          .iter()
+          .all(|term| (term.coefficient / first).fract() == 0.0)
+        {
+          first
+        } else {
+          1.0
+        };
+        for term in &mut terms {
+          term.coefficient /= content;
+        }
+        Term {
+          coefficient: content,
+          ..Term::factor(build(terms), 1.0)
+        }
+      }
+    }
+  }
+
+  // Like terms have the same factors with the same powers, in any order
+  fn key(&self) -> Vec<String> {
+    let mut key: Vec<String> = self
+      .factors
+      .iter()
+      .map(|(name, _, power)| format!("{} ^ {}", name, power))
+      .collect();
+    key.sort();
+    key
+  }
+
+  fn multiply(&self, other: &Term) -> Term {
+    let mut factors = self.factors.clone();
+    for (name, component, power) in &other.factors {
+      match factors.iter_mut().find(|(n, _, _)| n == name) {
+        Some(factor) => factor.2 += power,
+        None => factors.push((name.clone(), component.clone(), *power)),
+      }
+    }
+    factors.retain(|(_, _, power)| *power != 0.0);
+    Term {
+      coefficient: self.coefficient * other.coefficient,
+      factors,
+    }
+  }
+
+  // Integer powers only, so every factor keeps its domain
+  fn power(&self, power: Prec) -> Term {
+    Term {
+      coefficient: self.coefficient.powf(power),
+      factors: self
+        .factors
+        .iter()
+        .map(|(name, component, p)| (name.clone(), component.clone(), p * power))
+        .filter(|(_, _, p)| *p != 0.0)
+        .collect(),
+    }
+  }
+
+  fn to_component(&self, coefficient: Prec) -> Component {
+    let product = |positive: bool| {
+      self
+        .factors
+        .iter()
+        .filter(|(_, _, power)| (*power > 0.0) == positive)
+        .map(|(_, component, power)| {
+          if power.abs() == 1.0 {
+            component.clone()
+          } else {
+            create_binary(Exponent, component.clone(), Component::Number(power.abs()))
+          }
+        })
+        .reduce(|left, right| create_binary(Multiply, left, right))
+    };
+    let numerator = scale(coefficient, product(true));
+    match product(false) {
+      Some(denominator) if coefficient != 0.0 => create_binary(Divide, numerator, denominator),
+      _ => numerator,
+    }
+  }
+}
+
+// Splits a component into terms, merging like terms and powers of the same factor
+fn terms(component: &Component) -> Vec<Term> {
+  let (operator, values) = match component {
+    Component::Number(f) => return vec![Term::number(*f)],
+    Component::Function { operator, values } => (operator, values),
+    _ => return vec![Term::factor(component.clone(), 1.0)],
+  };
+
+  let mut terms = match (operator, values.as_slice()) {
+    (Add, [left, right]) => [terms(left), terms(right)].concat(),
+    (Subtract, [left, right]) => {
+      let mut right = terms(right);
+      for term in &mut right {
+        term.coefficient = -term.coefficient;
+      }
+      [terms(left), right].concat()
+    }
+    (Multiply, [left, right]) => {
+      let (left, right) = (terms(left), terms(right));
+      match (left.as_slice(), right.as_slice()) {
+        // Numbers are distributed over sums
+        ([number], terms) | (terms, [number]) if number.factors.is_empty() => terms
+          .iter()
+          .map(|term| Term {
+            coefficient: term.coefficient * number.coefficient,
+            ..term.clone()
+          })
+          .collect(),
+        _ => vec![Term::from_sum(left).multiply(&Term::from_sum(right))],
+      }
+    }
+    // Every term of the numerator is divided, so they collect with the terms around the quotient
+    (Divide, [left, right]) => {
+      let denominator = Term::from_sum(terms(right));
+      if denominator.coefficient == 0.0 || !denominator.coefficient.is_finite() {
+        vec![Term::factor(
+          create_binary(
+            Divide,
+            collect(left),
+            denominator.to_component(denominator.coefficient),
+          ),
+          1.0,
+        )]
+      } else {
+        let inverse = Term {
+          coefficient: 1.0 / denominator.coefficient,
+          ..denominator.power(-1.0)
+        };
+        // A numerator which is a multiple of a factor of the denominator cancels with it instead
+        let numerator = terms(left);
+        let common = Term::from_sum(numerator.clone());
+        if numerator.len() > 1
+          && common
+            .factors
+            .iter()
+            .any(|(name, _, _)| inverse.factors.iter().any(|(n, _, _)| n == name))
+        {
+          vec![common.multiply(&inverse)]
+        } else {
+          numerator
+            .iter()
+            .map(|term| term.multiply(&inverse))
+            .collect()
+        }
+      }
+    }
+    (Exponent, [base, Component::Number(power)]) | (Pow, [base, Component::Number(power)])
+      if power.fract() == 0.0 =>
+    {
+      let base = Term::from_sum(terms(base));
+      if base.coefficient == 0.0 && *power < 0.0 {
+        vec![Term::factor(
+          create_binary(Exponent, Component::Number(0.0), Component::Number(*power)),
+          1.0,
+        )]
+      } else {
+        vec![base.power(*power)]
+      }
+    }
+    _ => vec![Term::factor(
+      Component::Function {
+        operator: operator.clone(),
+        values: values.iter().map(collect).collect(),
+      },
+      1.0,
+    )],
+  };
+
+  // Merge like terms into the first one
+  let mut merged: Vec<(Vec<String>, Term)> = vec![];
+  for term in terms.drain(..) {
+    let key = term.key();
+    match merged.iter_mut().find(|(k, _)| *k == key) {
+      Some((_, like)) => like.coefficient += term.coefficient,
+      None => merged.push((key, term)),
+    }
+  }
+  merged
+    .into_iter()
+    .map(|(_, term)| term)
+    .filter(|term| term.coefficient != 0.0)
+    .collect()
+}
+
+fn build(terms: Vec<Term>) -> Component {
+  let mut sum: Option<Component> = None;
+  for term in terms {
+    sum = Some(match sum {
+      // x + -a * y, becomes x - a * y
+      Some(sum) if term.coefficient < 0.0 => {
+        create_binary(Subtract, sum, term.to_component(-term.coefficient))
+      }
+      Some(sum) => create_binary(Add, sum, term.to_component(term.coefficient)),
+      None => term.to_component(term.coefficient),
+    });
+  }
+  sum.unwrap_or(Component::Number(0.0))
+}
+
+// Collects like terms and merges integer powers of the same factor, like x * x ^ 2 - 2 * x ^ 3 to -1 * x ^ 3
+fn collect(component: &Component) -> Component {
+  build(terms(component))
+}
+
+// Simplifies until nothing changes, a single pass of parser::simplify leaves
+// trivial operations which only appear after their children were simplified
+pub(crate) fn simplify(component: &Component) -> Component {
+  let mut component = clean(component);
+  for _ in 0..MAX_PASSES {
+    let simplified = clean(&collect(&fold(&component)));
+    if simplified.to_string() == component.to_string() {
+      break;
+    }
+    component = simplified;
+  }
+  component
+}
+
+/// Derives a component, every variable is treated as the same variable
 pub fn derive_component(expr: &Component) -> Component {
+  derive(expr, None, &[])
+}
+
+/// Derives a component with respect to var, other variables are treated as constants
+pub fn derive_partial(expr: &Component, var: &str) -> Component {
+  derive(expr, Some(var), &[])
+}
+
+/// Derives a component with respect to every variable in vars in turn, simplifying between steps
+pub fn derive_partials(expr: &Component, vars: &[&str]) -> Component {
+  Derivatives::new(expr).get(vars)
+}
+
+// Bound variables of the sums and products around expr are constants
+fn derive(expr: &Component, var: Option<&str>, bound: &[&str]) -> Component {
+  let derive_component = |expr: &Component| derive(expr, var, bound);
+  let chain_rule =
+    |left: Component, right: &Component| create_binary(Multiply, left, derive(right, var, bound));
+
   match expr {
     Component::Number(_) => Component::Number(0.0),
-    Component::Variable(_) => Component::Number(1.0),
+    Component::Variable(c) => {
+      if bound.contains(&c.as_str()) {
+        Component::Number(0.0)
+      } else if var.is_none() || var == Some(c.as_str()) {
+        Component::Number(1.0)
+      } else {
+        Component::Number(0.0)
+      }
+    }
 
     Component::Function { operator, values } => match operator {
       Add => create_binary(
@@ -46,7 +460,7 @@ pub fn derive_component(expr: &Component) -> Component {
           create_binary(
             Multiply,
             create_unary(Ln, Component::Number(*f)),
-            chain_rule(expr.clone(), &expr),
+            chain_rule(expr.clone(), &values[1]),
           )
         } else {
           // x^x
@@ -70,24 +484,80 @@ pub fn derive_component(expr: &Component) -> Component {
         }
       }
 
-      Log => chain_rule(
+      Divide => create_binary(
+        Divide,
         create_binary(
-          Divide,
+          Subtract,
+          chain_rule(values[1].clone(), &values[0]),
+          chain_rule(values[0].clone(), &values[1]),
+        ),
+        create_binary(Exponent, values[1].clone(), Component::Number(2.0)),
+      ),
+
+      // a % b equals a - trunc(a / b) * b, the remainder takes the sign of a
+      // There is no trunc operator, but trunc(q) equals q - q % 1 since % truncates too
+      Modulo => {
+        let quotient = create_binary(Divide, values[0].clone(), values[1].clone());
+        create_binary(
+          Subtract,
           derive_component(&values[0]),
+          chain_rule(
+            create_binary(
+              Subtract,
+              quotient.clone(),
+              create_binary(Modulo, quotient, Component::Number(1.0)),
+            ),
+            &values[1],
+          ),
+        )
+      }
+
+      Log => {
+        let base = clean(&derive_component(&values[1]));
+        if base.to_float() == Some(0.0) {
           create_binary(
-            Multiply,
+            Divide,
+            derive_component(&values[0]),
+            create_binary(
+              Multiply,
+              create_unary(Ln, values[1].clone()),
+              values[0].clone(),
+            ),
+          )
+        } else {
+          // log(n, base) equals ln(n) / ln(base)
+          derive_component(&create_binary(
+            Divide,
+            create_unary(Ln, values[0].clone()),
             create_unary(Ln, values[1].clone()),
-            values[0].clone(),
-          ),
-        ),
-        &values[0],
+          ))
+        }
+      }
+
+      Ln => create_binary(Divide, derive_component(&values[0]), values[0].clone()),
+
+      Exp => chain_rule(expr.clone(), &values[0]),
+
+      Sqrt => create_binary(
+        Divide,
+        derive_component(&values[0]),
+        create_binary(Multiply, Component::Number(2.0), expr.clone()),
       ),
 
-      Ln => chain_rule(
-        create_binary(Divide, derive_component(&values[0]), values[0].clone()),
+      // root(n, k) equals n ^ (1 / k)
+      Root => derive_component(&create_binary(
+        Exponent,
+        values[0].clone(),
+        create_binary(Divide, Component::Number(1.0), values[1].clone()),
+      )),
+
+      Abs => chain_rule(
+        create_binary(Divide, values[0].clone(), expr.clone()),
         &values[0],
       ),
 
+      Floor | Round | Ceil => Component::Number(0.0),
+
       Sin => chain_rule(create_unary(Cos, values[0].clone()), &values[0]),
 
       Cos => chain_rule(
@@ -143,6 +613,43 @@ pub fn derive_component(expr: &Component) -> Component {
         &values[0],
       ),
 
+      Sum | Product => {
+        let k = match expr.bound_variable() {
+          Some(k) => k,
+          None => return Component::End,
+        };
+        // Bounds which change with the variable add or remove terms
+        if values[2..]
+          .iter()
+          .any(|bound| clean(&derive_component(bound)).to_float() != Some(0.0))
+        {
+          return Component::End;
+        }
+
+        let mut inner = bound.to_vec();
+        inner.push(k);
+        let term = derive(&values[0], var, &inner);
+        let sum = |term: Component| Component::Function {
+          operator: Sum,
+          values: vec![
+            term,
+            values[1].clone(),
+            values[2].clone(),
+            values[3].clone(),
+          ],
+        };
+        if operator.compare(&Sum) {
+          sum(term)
+        } else {
+          // The logarithmic derivative of a product is the sum of the logarithmic derivatives
+          create_binary(
+            Multiply,
+            expr.clone(),
+            sum(create_binary(Divide, term, values[0].clone())),
+          )
+        }
+      }
+
       _ => Component::End,
     },
     _ => Component::End,
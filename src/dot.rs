@@ -0,0 +1,67 @@
+use super::component::Component;
+
+// Attributes of the nodes and edges on the path to the highlighted variable
+const HIGHLIGHT: &str = ", color=red, fontcolor=red, penwidth=2";
+
+fn escape(text: &str) -> String {
+  text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+struct Graph<'a> {
+  lines: Vec<String>,
+  nodes: usize,
+  highlight: Option<&'a str>,
+}
+
+impl Graph<'_> {
+  // Adds the node of a component and its arguments, returns its id and whether it leads to the highlighted variable
+  fn add(&mut self, component: &Component) -> (usize, bool) {
+    let id = self.nodes;
+    self.nodes += 1;
+    // The node is written once it is known whether it is highlighted, the arguments are added in between
+    let line = self.lines.len();
+    self.lines.push(String::new());
+
+    let (label, shape, mut highlighted) = match component {
+      Component::Variable(c) => (c.to_string(), "box", self.highlight == Some(c.as_str())),
+      Component::Number(f) => (f.to_string(), "box", false),
+      Component::Function { operator, .. } => (operator.to_string(), "ellipse", false),
+      Component::End => (String::from("end"), "point", false),
+    };
+
+    if let Component::Function { values, .. } = component {
+      for (i, value) in values.iter().enumerate() {
+        let (child, on_path) = self.add(value);
+        highlighted |= on_path;
+        self.lines.push(format!(
+          "  n{} -> n{} [label=\"{}\"{}];",
+          id,
+          child,
+          i,
+          if on_path { HIGHLIGHT } else { "" }
+        ));
+      }
+    }
+
+    self.lines[line] = format!(
+      "  n{} [label=\"{}\", shape={}{}];",
+      id,
+      escape(&label),
+      shape,
+      if highlighted { HIGHLIGHT } else { "" }
+    );
+    (id, highlighted)
+  }
+}
+
+/// Converts a component to a Graphviz digraph, optionally highlighting the paths to a variable
+pub fn to_dot(component: &Component, highlight: Option<&str>) -> String {
+  let mut graph = Graph {
+    lines: vec![String::from("digraph {")],
+    nodes: 0,
+    highlight,
+  };
+  graph.add(component);
+  graph.lines.push(String::from("}"));
+  graph.lines.join("\n")
+}
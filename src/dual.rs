@@ -0,0 +1,62 @@
+use super::component::{Component, Prec};
+use super::summation;
+
+// A value together with its partial derivatives with respect to every variable
+#[derive(Debug, Clone)]
+struct Dual {
+  value: Prec,
+  gradient: Vec<Prec>,
+}
+
+impl Dual {
+  fn constant(value: Prec, size: usize) -> Dual {
+    Dual {
+      value,
+      gradient: vec![0.0; size],
+    }
+  }
+}
+
+fn evaluate(component: &Component, vars: &[(&str, Prec)]) -> Option<Dual> {
+  match component {
+    Component::Number(f) => Some(Dual::constant(*f, vars.len())),
+    Component::Variable(c) => {
+      let i = vars.iter().position(|(v, _)| v == c)?;
+      let mut dual = Dual::constant(vars[i].1, vars.len());
+      dual.gradient[i] = 1.0;
+      Some(dual)
+    }
+    Component::Function { operator, values } => {
+      let duals = values
+        .iter()
+        .map(|x| evaluate(x, vars))
+        .collect::<Option<Vec<_>>>()?;
+      let values: Vec<_> = duals.iter().map(|dual| dual.value).collect();
+      let value = operator.apply(&values)?;
+      let partials = operator.partials(&values)?;
+
+      // Chain rule, zero derivatives are skipped to prevent 0 * inf from turning into NaN
+      // like the derivative of 2 ^ x for the base
+      let mut gradient = vec![0.0; vars.len()];
+      for (dual, partial) in duals.iter().zip(partials) {
+        for (d, derivative) in gradient.iter_mut().zip(&dual.gradient) {
+          if *derivative != 0.0 {
+            *d += partial * derivative;
+          }
+        }
+      }
+
+      Some(Dual { value, gradient })
+    }
+    Component::End => None,
+  }
+}
+
+/// Evaluates a component and its gradient in one pass with forward mode automatic differentiation
+/// The gradient is in the order of vars, returns None if the component contains other variables
+pub fn evaluate_with_gradient(
+  component: &Component,
+  vars: &[(&str, Prec)],
+) -> Option<(Prec, Vec<Prec>)> {
+  evaluate(&summation::unroll(component)?, vars).map(|dual| (dual.value, dual.gradient))
+}
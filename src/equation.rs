@@ -1,15 +1,38 @@
 use std::collections::HashMap;
 
+use super::codegen;
+use super::codegen::Language;
 use super::component::{Component, Prec};
 use super::derive;
+use super::derive::Derivatives;
+use super::dual;
+use super::evaluate::Compiled;
+use super::extrema;
+use super::extrema::Extrema;
+use super::integrate;
+use super::latex;
+use super::limit;
+use super::limit::Direction;
+use super::ode;
+use super::ode::{Solution, Solver};
 use super::operators::{Operator, Operator::*};
 use super::parser;
+use super::quadrature;
+use super::quadrature::{Integral, Quadrature};
+use super::rational;
+use super::summation;
+use super::tape::Tape;
+use super::taylor;
+use super::utils::create_binary;
 
 /// The equation struct containing the equation text and the parsed component.Component.
 ///
 /// Various functions can be executed on this equation to solve it or it's variables.
+///
+/// With the `serde` feature, equations are serialized as an object with the `text` and the `expression`.
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Equation {
   /// The equation in string form
   pub text: String,
@@ -39,6 +62,24 @@ impl Equation {
     Equation { text, expression }
   }
 
+  /// Creates a new equation from LaTeX, like formulas copied from papers
+  /// Supports fractions, roots, powers, subscripts, functions like \\sin and greek letters as variable names
+  /// Returns None if the LaTeX contains unsupported commands or unbalanced groups
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::Equation;
+  ///
+  /// let eq = Equation::from_latex(r"\frac{x^2}{2} + \sqrt{y}").unwrap();
+  ///
+  /// assert_eq!(eq.text, "x ^ 2 / 2 + sqrt(y)");
+  /// assert_eq!(eq.solve_with(vec![("x", 4.0), ("y", 9.0)]).to_float().unwrap(), 11.0);
+  /// assert_eq!(Equation::from_latex(r"2\alpha_{1} \cdot \sin\left(\theta\right)").unwrap().text, "2 * alpha_1 * sin(theta)");
+  /// ```
+  pub fn from_latex(latex: &str) -> Option<Equation> {
+    latex::from_latex(latex).map(Equation::new)
+  }
+
   // fn single_unknown_variable(epxr: &Component) -> bool {
   //   false
   // }
@@ -52,54 +93,13 @@ impl Equation {
   // }
 
   fn apply_function(operator: &Operator, values: &[Component]) -> Option<Component> {
-    if values.is_empty() {
-      return None;
-    }
-
-    // Unary operators
-    if values.len() == 1 {
-      if let Component::Number(f) = &values[0] {
-        return Some(match operator {
-          Sin => Component::Number(f.sin()),
-          Cos => Component::Number(f.cos()),
-          Tan => Component::Number(f.tan()),
-          Sec => Component::Number(1.0 / f.cos()),
-          Csc => Component::Number(1.0 / f.sin()),
-          Cot => Component::Number(1.0 / f.tan()),
-          Abs => Component::Number(f.abs()),
-          Floor => Component::Number(f.floor()),
-          Round => Component::Number(f.round()),
-          Ceil => Component::Number(f.ceil()),
-          Exp => Component::Number(f.exp()),
-          Ln => Component::Number(f.ln()),
-          Sqrt => Component::Number(f.sqrt()),
-          _ => return None,
-        });
-      }
-    }
-
-    // Apply binary operator to components if they are both numbers
-    if values.len() == 2 {
-      if let (Component::Number(f1), Component::Number(f2)) = (&values[0], &values[1]) {
-        match operator {
-          Add => return Some(Component::Number(f1 + f2)),
-          Subtract => return Some(Component::Number(f1 - f2)),
-          Multiply => return Some(Component::Number(f1 * f2)),
-          Divide => return Some(Component::Number(f1 / f2)),
-          Modulo => return Some(Component::Number(f1 % f2)),
-          Exponent | Pow => return Some(Component::Number(f1.powf(*f2))),
-          Log => return Some(Component::Number(f1.log(*f2))),
-          Root => return Some(Component::Number(f1.powf(1.0 / *f2))),
-          _ => (),
-        }
-      }
-    }
-
-    None
+    // Operators can only be applied if all values are numbers
+    let numbers: Option<Vec<_>> = values.iter().map(|x| x.to_float()).collect();
+    operator.apply(&numbers?).map(Component::Number)
   }
 
   // Attempt to solve component with given variables
-  fn solve_component(vars: &HashMap<&str, Prec>, component: &Component) -> Component {
+  pub(crate) fn solve_component(vars: &HashMap<&str, Prec>, component: &Component) -> Component {
     match component {
       // Attempt to retrieve variable value
       Component::Variable(c) => {
@@ -112,6 +112,11 @@ impl Equation {
 
       Component::Number(f) => Component::Number(*f),
 
+      // Sums and products bind their own variable
+      Component::Function { operator, values } if component.bound_variable().is_some() => {
+        summation::solve(vars, operator, values)
+      }
+
       // Attempt to solve binary component
       Component::Function { operator, values } => {
         // Retrieve value of left and right component
@@ -119,17 +124,14 @@ impl Equation {
           .iter()
           .map(|x| Self::solve_component(vars, x))
           .collect();
-        
-        let solved = Self::apply_function(&operator, &values);
 
-        if solved.is_some() {
-          solved.unwrap()
-        } else {
+        match Self::apply_function(operator, &values) {
+          Some(solved) => solved,
           // Return original binary component if simplifying failed
-          Component::Function {
+          None => Component::Function {
             operator: operator.clone(),
             values,
-          }
+          },
         }
       }
       _ => Component::End,
@@ -253,12 +255,468 @@ impl Equation {
   /// ```
   /// let eq = calculi::Equation::new("x^sin(x)").derive();
   ///
-  /// assert_eq!(eq.text, "x ^ sin(x) * (cos(x) * ln(x) + sin(x) * 1 / x)");
+  /// assert_eq!(eq.text, "x ^ sin(x) * (cos(x) * ln(x) + sin(x) * (1 / x))");
   /// ```
   pub fn derive(&self) -> Equation {
     Equation::from(parser::simplify(derive::derive_component(&self.expression)))
   }
 
+  /// Get the n-th derivative of an equation with respect to var, other variables are treated as constants
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("x ^ 4 + y * x");
+  ///
+  /// assert_eq!(eq.derive_n("x", 2).text, "12 * x ^ 2");
+  /// assert_eq!(eq.derive_n("x", 5).text, "0");
+  /// ```
+  pub fn derive_n(&self, var: &str, n: usize) -> Equation {
+    Equation::from(derive::derive_partials(&self.expression, &vec![var; n]))
+  }
+
+  /// Get the mixed partial derivative of an equation, derived with respect to every variable in vars
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("x ^ 2 * y ^ 3");
+  ///
+  /// assert_eq!(eq.derive_partials(&["x", "y"]).text, "6 * (x * y ^ 2)");
+  /// assert_eq!(eq.derive_partials(&["y", "x"]).text, "6 * (x * y ^ 2)");
+  /// ```
+  pub fn derive_partials(&self, vars: &[&str]) -> Equation {
+    Equation::from(derive::derive_partials(&self.expression, vars))
+  }
+
+  /// Get the derivative of y with respect to x for the implicit curve where the equation equals zero
+  /// The curve is written as F(x, y) = 0, and the derivative equals -F_x / F_y
+  /// Returns None if F does not depend on y
+  ///
+  /// # Examples
+  /// ```
+  /// let circle = calculi::Equation::new("x ^ 2 + y ^ 2 - 25");
+  ///
+  /// assert_eq!(circle.implicit_derivative("y", "x").unwrap().text, "-1 * x / y");
+  /// ```
+  pub fn implicit_derivative(&self, y: &str, x: &str) -> Option<Equation> {
+    let mut derivatives = Derivatives::new(&self.expression);
+    let (dx, dy) = (derivatives.get(&[x]), derivatives.get(&[y]));
+    if dy.to_float() == Some(0.0) {
+      return None;
+    }
+
+    let quotient = create_binary(
+      Divide,
+      create_binary(Multiply, Component::Number(-1.0), dx),
+      dy,
+    );
+    Some(Equation::from(derive::simplify(&rational::cancel(
+      &quotient,
+    ))))
+  }
+
+  /// Get the derivative of y with respect to x for the implicit curve where the equation equals right
+  /// The curve is written as F(x, y) = equation - right = 0, see [implicit_derivative](#method.implicit_derivative)
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::Equation;
+  ///
+  /// let circle = Equation::new("x ^ 2 + y ^ 2");
+  ///
+  /// assert_eq!(circle.implicit_derivative_with(&Equation::new("25"), "y", "x").unwrap().text, "-1 * x / y");
+  /// assert_eq!(Equation::new("x * y").implicit_derivative_with(&Equation::new("1"), "y", "x").unwrap().text, "-1 * y / x");
+  /// ```
+  pub fn implicit_derivative_with(&self, right: &Equation, y: &str, x: &str) -> Option<Equation> {
+    Equation::from(create_binary(
+      Subtract,
+      self.expression.clone(),
+      right.expression.clone(),
+    ))
+    .implicit_derivative(y, x)
+  }
+
+  /// Get the gradient of an equation, the partial derivatives with respect to every variable in vars
+  ///
+  /// # Examples
+  /// ```
+  /// let gradient = calculi::Equation::new("x ^ 2 * y + z").gradient(&["x", "y", "z"]);
+  /// let texts: Vec<_> = gradient.iter().map(|eq| eq.text.as_str()).collect();
+  ///
+  /// assert_eq!(texts, vec!["2 * (y * x)", "x ^ 2", "1"]);
+  /// ```
+  pub fn gradient(&self, vars: &[&str]) -> Vec<Equation> {
+    let mut derivatives = Derivatives::new(&self.expression);
+    vars
+      .iter()
+      .map(|var| Equation::from(derivatives.get(&[var])))
+      .collect()
+  }
+
+  /// Get the jacobian matrix of equations, every row is the gradient of an equation
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::Equation;
+  ///
+  /// let jacobian = Equation::jacobian(&[Equation::new("x * y"), Equation::new("x + 3 * y")], &["x", "y"]);
+  ///
+  /// assert_eq!(jacobian[0][0].text, "y");
+  /// assert_eq!(jacobian[1][1].text, "3");
+  /// ```
+  pub fn jacobian(equations: &[Equation], vars: &[&str]) -> Vec<Vec<Equation>> {
+    equations.iter().map(|eq| eq.gradient(vars)).collect()
+  }
+
+  /// Get the hessian matrix of an equation, the second order partial derivatives with respect to vars
+  /// Mixed partial derivatives are only calculated once, the matrix is symmetric
+  ///
+  /// # Examples
+  /// ```
+  /// let hessian = calculi::Equation::new("x ^ 3 * y").hessian(&["x", "y"]);
+  ///
+  /// assert_eq!(hessian[0][0].text, "6 * (y * x)");
+  /// assert_eq!(hessian[0][1].text, "3 * x ^ 2");
+  /// assert_eq!(hessian[1][1].text, "0");
+  /// ```
+  pub fn hessian(&self, vars: &[&str]) -> Vec<Vec<Equation>> {
+    let mut derivatives = Derivatives::new(&self.expression);
+    vars
+      .iter()
+      .map(|x| {
+        vars
+          .iter()
+          .map(|y| Equation::from(derivatives.get(&[x, y])))
+          .collect()
+      })
+      .collect()
+  }
+
+  /// Evaluates every equation of a matrix with the given variable definitions, like a jacobian or hessian
+  /// Returns None if an equation could not be solved to a number
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::Equation;
+  ///
+  /// let hessian = Equation::new("x ^ 3 * y").hessian(&["x", "y"]);
+  ///
+  /// assert_eq!(
+  ///   Equation::evaluate_matrix(&hessian, vec![("x", 2.0), ("y", 1.0)]),
+  ///   Some(vec![vec![12.0, 12.0], vec![12.0, 0.0]])
+  /// );
+  /// ```
+  pub fn evaluate_matrix<'a>(
+    matrix: &[Vec<Equation>],
+    vars_raw: impl IntoIterator<Item = (&'a str, Prec)>,
+  ) -> Option<Vec<Vec<f64>>> {
+    let vars: HashMap<_, _> = vars_raw.into_iter().collect();
+    matrix
+      .iter()
+      .map(|row| {
+        row
+          .iter()
+          .map(|eq| {
+            Self::solve_component(&vars, &eq.expression)
+              .to_float()
+              .map(f64::from)
+          })
+          .collect()
+      })
+      .collect()
+  }
+
+  /// Combines all fractions of an equation into a single fraction
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("x / (x + 1) + 1 / (x + 1)").together();
+  ///
+  /// assert_eq!(eq.text, "(x + 1) / (x + 1)");
+  /// ```
+  pub fn together(&self) -> Equation {
+    Equation::from(Self::solve_component(
+      &HashMap::new(),
+      &rational::together(&self.expression),
+    ))
+  }
+
+  /// Combines all fractions of an equation and cancels common factors of the numerator and denominator
+  /// Factors are only cancelled when the numerator and denominator are polynomials
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("(x ^ 2 - 1) / (x - 1)").cancel();
+  ///
+  /// assert_eq!(eq.text, "x + 1");
+  ///
+  /// let eq = calculi::Equation::new("x / (x ^ 2 + x) + 1 / (x + 1)").cancel();
+  ///
+  /// assert_eq!(eq.text, "2 / (x + 1)");
+  ///
+  /// let eq = calculi::Equation::new("(x ^ 2 - y ^ 2) / (x - y)").cancel();
+  ///
+  /// assert_eq!(eq.text, "x + y");
+  /// ```
+  pub fn cancel(&self) -> Equation {
+    Equation::from(Self::solve_component(
+      &HashMap::new(),
+      &rational::cancel(&self.expression),
+    ))
+  }
+
+  /// Decomposes a rational function of the given variable into partial fractions
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("(3 * x + 1) / (x ^ 2 - 1)").apart("x");
+  ///
+  /// assert_eq!(eq.text, "2 / (x - 1) + 1 / (x + 1)");
+  /// ```
+  pub fn apart(&self, var: &str) -> Equation {
+    Equation::from(Self::solve_component(
+      &HashMap::new(),
+      &rational::apart(&self.expression, var),
+    ))
+  }
+
+  /// Get the indefinite integral (primitive) of an equation with respect to the given variable
+  /// Returns None if no antiderivative could be found, or if the equation contains floor, round, ceil or % of the variable
+  ///
+  /// Other variables are treated as constants and the constant of integration is left out.
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("3 * x ^ 2 + 2 * x").integrate_wrt("x").unwrap();
+  ///
+  /// assert_eq!(eq.text, "x ^ 3 + x ^ 2");
+  ///
+  /// let eq = calculi::Equation::new("2 * x * cos(x ^ 2)").integrate_wrt("x").unwrap();
+  ///
+  /// assert_eq!(eq.text, "sin(x ^ 2)");
+  ///
+  /// assert!(calculi::Equation::new("sin(x) / x").integrate_wrt("x").is_none());
+  /// assert!(calculi::Equation::new("floor(x)").integrate_wrt("x").is_none());
+  /// ```
+  pub fn integrate_wrt(&self, var: &str) -> Option<Equation> {
+    integrate::integrate_component(&self.expression, var).map(Equation::from)
+  }
+
+  /// Get the limit of an equation when var approaches point from the given direction
+  ///
+  /// The point may be infinite, infinite limits are returned as infinite floats.
+  /// Returns None if the limit does not exist or the equation contains other variables.
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::{Direction, Equation};
+  ///
+  /// assert_eq!(Equation::new("sin(x) / x").limit("x", 0.0, Direction::Both), Some(1.0));
+  /// assert_eq!(Equation::new("(1 + 1 / x) ^ x").limit("x", std::f32::INFINITY, Direction::Both), Some(std::f32::consts::E));
+  /// assert_eq!(Equation::new("1 / x").limit("x", 0.0, Direction::Left), Some(std::f32::NEG_INFINITY));
+  /// assert_eq!(Equation::new("1 / x").limit("x", 0.0, Direction::Both), None);
+  /// ```
+  pub fn limit(&self, var: &str, point: Prec, direction: Direction) -> Option<Prec> {
+    limit::limit(&self.expression, var, point, direction)
+  }
+
+  /// Find the critical points of an equation on the closed interval [a, b], classified with the second derivative
+  /// Also finds the inflection points and the global extrema, which may be at the endpoints
+  /// Returns None if the equation contains other variables or the interval is not finite
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::{Classification, Equation};
+  ///
+  /// let extrema = Equation::new("x ^ 3 - 3 * x").critical_points("x", (-3.0, 3.0)).unwrap();
+  ///
+  /// assert_eq!(extrema.critical_points[0].x, -1.0);
+  /// assert_eq!(extrema.critical_points[0].classification, Classification::Maximum);
+  /// assert_eq!(extrema.critical_points[1].classification, Classification::Minimum);
+  /// assert_eq!(extrema.inflection_points, vec![0.0]);
+  /// assert_eq!(extrema.minimum, (-3.0, -18.0));
+  /// assert_eq!(extrema.maximum, (3.0, 18.0));
+  /// ```
+  pub fn critical_points(&self, var: &str, interval: (Prec, Prec)) -> Option<Extrema> {
+    extrema::extrema(&self.expression, var, interval)
+  }
+
+  /// Get the taylor polynomial of an equation around about, up to and including the given order
+  /// Returns None if a derivative is not finite at the point
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("sin(x)").taylor("x", 0.0, 5).unwrap();
+  ///
+  /// assert_eq!(eq.text, "x - x ^ 3 / 6 + x ^ 5 / 120");
+  /// assert_eq!(calculi::Equation::new("exp(x)").taylor("x", 1.0, 1).unwrap().text, "2.7182817 + 2.7182817 * (x - 1)");
+  /// ```
+  pub fn taylor(&self, var: &str, about: Prec, order: usize) -> Option<Equation> {
+    taylor::taylor(&self.expression, var, about, order).map(Equation::from)
+  }
+
+  /// Bound the error of the taylor polynomial of the given order at x
+  /// The bound is the lagrange remainder, with the maximum of the next derivative between about and x
+  /// bounded by interval arithmetic
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("sin(x)");
+  /// let bound = eq.remainder_bound("x", 0.0, 5, 0.5).unwrap();
+  /// let error = (0.5f32.sin() - eq.taylor("x", 0.0, 5).unwrap().solve_with(vec![("x", 0.5)]).to_float().unwrap()).abs();
+  ///
+  /// assert!(error <= bound && bound < 1e-4);
+  /// ```
+  pub fn remainder_bound(&self, var: &str, about: Prec, order: usize, x: Prec) -> Option<Prec> {
+    taylor::remainder_bound(&self.expression, var, about, order, x)
+  }
+
+  /// Numerically approximate the definite integral from a to b with adaptive Gauss-Kronrod quadrature
+  /// Bounds may be infinite, returns None if the equation contains other variables or the approximation is not finite
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("exp(0 - x ^ 2)");
+  ///
+  /// let integral = eq.integrate_numeric("x", std::f32::NEG_INFINITY, std::f32::INFINITY).unwrap();
+  ///
+  /// assert!((integral.value - std::f32::consts::PI.sqrt()).abs() < 1e-4);
+  /// assert!(integral.error < 1e-4);
+  /// ```
+  pub fn integrate_numeric(&self, var: &str, a: Prec, b: Prec) -> Option<Integral> {
+    self.integrate_numeric_with(var, a, b, Quadrature::GaussKronrod)
+  }
+
+  /// Numerically approximate the definite integral from a to b with the given method
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::{Equation, Quadrature};
+  ///
+  /// let eq = Equation::new("sin(x)");
+  ///
+  /// let integral = eq.integrate_numeric_with("x", 0.0, std::f32::consts::PI, Quadrature::Simpson).unwrap();
+  ///
+  /// assert!((integral.value - 2.0).abs() < 1e-4);
+  /// ```
+  pub fn integrate_numeric_with(
+    &self,
+    var: &str,
+    a: Prec,
+    b: Prec,
+    method: Quadrature,
+  ) -> Option<Integral> {
+    let compiled = Compiled::new(&self.expression, &[var])?;
+    quadrature::integrate(&|x| compiled.call(&[x]), a, b, method)
+  }
+
+  /// Get the output of an equation and its gradient with respect to the given variables in one pass
+  /// Uses automatic differentiation, so no derivative equations are built
+  /// Returns None if the equation contains variables which are not defined
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("x ^ 2 * y + sin(y)");
+  ///
+  /// let (value, gradient) = eq.eval_with_gradient(vec![("x", 3.0), ("y", 0.0)]).unwrap();
+  ///
+  /// assert_eq!(value, 0.0);
+  /// assert_eq!(gradient, vec![0.0, 10.0]);
+  /// ```
+  pub fn eval_with_gradient<'a>(
+    &self,
+    vars_raw: impl IntoIterator<Item = (&'a str, Prec)>,
+  ) -> Option<(Prec, Vec<Prec>)> {
+    let vars: Vec<_> = vars_raw.into_iter().collect();
+    dual::evaluate_with_gradient(&self.expression, &vars)
+  }
+
+  /// Record an equation on a tape for reverse mode automatic differentiation
+  /// Faster than eval_with_gradient for many variables, see Tape
+  /// Returns None if the equation contains other variables than vars
+  pub fn tape(&self, vars: &[&str]) -> Option<Tape> {
+    Tape::new(&self.expression, vars)
+  }
+
+  /// Generates a rust function with an f64 parameter for every variable in vars, which evaluates the equation
+  /// Returns None if the equation contains other variables, see rust_functions for whole files with derivatives
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("sqrt(x ^ 2 + y ^ 2) / sec(x)");
+  ///
+  /// assert_eq!(
+  ///   eq.to_rust_fn("length", &["x", "y"]).unwrap(),
+  ///   "fn length(x: f64, y: f64) -> f64 {\n    (x.powf(2.0) + y.powf(2.0)).sqrt() / (1.0 / x.cos())\n}\n"
+  /// );
+  /// ```
+  pub fn to_rust_fn(&self, name: &str, vars: &[&str]) -> Option<String> {
+    codegen::rust_fn(&self.expression, name, vars)
+  }
+
+  /// Generates a C99, GLSL or WGSL function with a parameter for every variable in vars, which evaluates the equation
+  /// Values which occur more than once are computed once and stored in temporaries
+  /// Returns None if the equation contains other variables
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::{Equation, Language};
+  ///
+  /// let eq = Equation::new("sin(x * y) / (1 + sin(x * y)) + log(x, 2)");
+  ///
+  /// assert_eq!(
+  ///   eq.to_code(Language::C, "f", &["x", "y"]).unwrap(),
+  ///   "double f(double x, double y) {
+  ///     const double t0 = sin(x * y);
+  ///     return t0 / (1.0 + t0) + log(x) / log(2.0);
+  /// }
+  /// "
+  /// );
+  /// assert_eq!(
+  ///   eq.to_code(Language::Wgsl, "f", &["x", "y"]).unwrap(),
+  ///   "fn f(x: f32, y: f32) -> f32 {
+  ///     let t0 = sin(x * y);
+  ///     return t0 / (1.0 + t0) + log(x) / log(2.0);
+  /// }
+  /// "
+  /// );
+  /// ```
+  pub fn to_code(&self, language: Language, name: &str, vars: &[&str]) -> Option<String> {
+    codegen::to_code(&self.expression, language, name, vars)
+  }
+
+  /// Solve the ordinary differential equation d var / d time = equation, starting at t0 with var equal to y0
+  /// Returns None if the equation contains other variables or the solution is not finite, see solve_ode for systems
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::{Equation, Solver};
+  ///
+  /// let eq = Equation::new("0 - 2 * y + sin(t)");
+  /// let solution = eq.solve_ode("y", "t", 0.0, 1.0, 2.0, Solver::Rk4 { step: 0.01 }).unwrap();
+  ///
+  /// assert_eq!(solution.times.len(), 201);
+  /// assert!((solution.states[200][0] - 0.4689).abs() < 1e-3);
+  /// ```
+  pub fn solve_ode(
+    &self,
+    var: &str,
+    time: &str,
+    t0: Prec,
+    y0: Prec,
+    end: Prec,
+    solver: Solver,
+  ) -> Option<Solution> {
+    ode::solve_ode(
+      std::slice::from_ref(self),
+      &[var],
+      time,
+      t0,
+      &[y0],
+      end,
+      solver,
+    )
+  }
+
   /// Get the output of an equation with the given variable definitions
   ///
   /// # Examples
@@ -284,7 +742,6 @@ impl Equation {
   /// assert_eq!(solved.0.to_string(), "a");
   /// assert_eq!(solved.1, 3.0);
   /// ```
-
   // TODO: Make solve_for with outcome as a component
   pub fn solve_for<'a>(
     &self,
@@ -305,4 +762,16 @@ impl Equation {
 
     expr
   }
+
+  /// Converts the equation to LaTeX, see [Component::to_latex](../component/enum.Component.html#method.to_latex)
+  ///
+  /// # Examples
+  /// ```
+  /// let eq = calculi::Equation::new("sin(x) / x");
+  ///
+  /// assert_eq!(eq.derive().to_latex(), "\\frac{x \\cdot \\cos\\left(x\\right) - \\sin\\left(x\\right)}{x^{2}}");
+  /// ```
+  pub fn to_latex(&self) -> String {
+    self.expression.to_latex()
+  }
 }
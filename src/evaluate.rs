@@ -0,0 +1,60 @@
+use super::component::{Component, Prec};
+use super::summation;
+
+type Function = Box<dyn Fn(&[Prec]) -> Prec>;
+
+// A component compiled to a tree of closures, with variables resolved to indices
+// Much faster than solving the component when it has to be evaluated many times
+pub(crate) struct Compiled {
+  function: Function,
+}
+
+impl Compiled {
+  // Compiles component, the values passed to call are in the order of vars
+  // Returns None if the component contains other variables or invalid parts
+  pub(crate) fn new(component: &Component, vars: &[&str]) -> Option<Compiled> {
+    Some(Compiled {
+      function: Self::compile(&summation::unroll(component)?, vars)?,
+    })
+  }
+
+  fn compile(component: &Component, vars: &[&str]) -> Option<Function> {
+    match component {
+      Component::Number(f) => {
+        let f = *f;
+        Some(Box::new(move |_| f))
+      }
+      Component::Variable(c) => {
+        let i = vars.iter().position(|v| v == c)?;
+        Some(Box::new(move |values| values[i]))
+      }
+      Component::Function { operator, values } => {
+        let operator = operator.clone();
+        let mut values = values
+          .iter()
+          .map(|x| Self::compile(x, vars))
+          .collect::<Option<Vec<_>>>()?;
+
+        // Check if the operator accepts the amount of values
+        operator.apply(&vec![1.0; values.len()])?;
+
+        Some(match values.len() {
+          1 => {
+            let value = values.pop().unwrap();
+            Box::new(move |x| operator.apply(&[value(x)]).unwrap())
+          }
+          _ => {
+            let right = values.pop().unwrap();
+            let left = values.pop().unwrap();
+            Box::new(move |x| operator.apply(&[left(x), right(x)]).unwrap())
+          }
+        })
+      }
+      Component::End => None,
+    }
+  }
+
+  pub(crate) fn call(&self, values: &[Prec]) -> Prec {
+    (self.function)(values)
+  }
+}
@@ -0,0 +1,329 @@
+use super::component::{Component, Prec};
+use super::derive::Derivatives;
+use super::equation::Equation;
+use super::evaluate::Compiled;
+use super::limit::{self, Direction};
+use super::polynomial::{snap, Polynomial};
+
+// Amount of subintervals in which the interval is scanned for roots
+const SAMPLES: usize = 1000;
+
+/// The kind of a critical point, found with the second derivative
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Classification {
+  /// The function is lower than around the point
+  Minimum,
+  /// The function is higher than around the point
+  Maximum,
+  /// The function keeps increasing or decreasing through the point, like x ^ 3 at 0
+  Saddle,
+}
+
+/// A point where the derivative is zero
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CriticalPoint {
+  pub x: Prec,
+  pub value: Prec,
+  pub classification: Classification,
+}
+
+/// The critical points, inflection points and global extrema of a function on a closed interval
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extrema {
+  /// Points where the derivative is zero, sorted from low to high
+  pub critical_points: Vec<CriticalPoint>,
+  /// Points where the second derivative changes sign, sorted from low to high
+  pub inflection_points: Vec<Prec>,
+  /// The lowest point on the interval as (x, value), which may be an endpoint
+  /// The value is negative infinity when the function is unbounded below near a pole,
+  /// and the value approached at a jump like x % 1 at 1 counts as well
+  pub minimum: (Prec, Prec),
+  /// The highest point on the interval as (x, value), which may be an endpoint
+  /// The value is infinity when the function is unbounded above near a pole,
+  /// and the value approached at a jump like x % 1 at 1 counts as well
+  pub maximum: (Prec, Prec),
+}
+
+// The i-th of the SAMPLES + 1 evenly spaced points on [a, b], without overflowing for intervals wider than Prec::MAX
+fn sample((a, b): (Prec, Prec), i: usize) -> Prec {
+  let t = i as Prec / SAMPLES as Prec;
+  a * (1.0 - t) + b * t
+}
+
+// Finds the roots of a component on the interval, symbolically if possible and numerically otherwise
+// Also returns the points where the sign changes without passing zero, like 1 / x at 0
+fn roots(component: &Component, var: &str, (a, b): (Prec, Prec)) -> Option<(Vec<Prec>, Vec<Prec>)> {
+  let compiled = Compiled::new(component, &[var])?;
+  let f = |x: Prec| compiled.call(&[x]);
+  let step = b / SAMPLES as Prec - a / SAMPLES as Prec;
+  let mut roots = vec![];
+  let mut jumps = vec![];
+
+  // Polynomials have an exact amount of real roots
+  if let Some(polynomial) = Polynomial::<Prec>::from_component(component) {
+    if polynomial.is_zero() {
+      return Some((vec![], vec![]));
+    }
+    if let Some(all) = polynomial.roots() {
+      let roots = all.into_iter().filter(|x| *x >= a && *x <= b);
+      return Some((roots.map(|x| x + 0.0).collect(), vec![]));
+    }
+  }
+
+  // The solver finds a root when the variable occurs once
+  let (solved, x) = Equation::from(component.clone()).solve_for(0.0, vec![]);
+  if let Component::Variable(c) = solved {
+    if c == var && x >= a && x <= b && f(x).abs() < 1e-4 {
+      roots.push(x);
+    }
+  }
+
+  // Scan for sign changes and for points which touch zero without changing sign
+  let samples: Vec<_> = (0..=SAMPLES)
+    .map(|i| {
+      let x = sample((a, b), i);
+      (x, f(x))
+    })
+    .collect();
+
+  for (i, window) in samples.windows(2).enumerate() {
+    let ((mut low, f_low), (mut high, f_high)) = (window[0], window[1]);
+    if f_low == 0.0 {
+      roots.push(low);
+    } else if f_low.signum() != f_high.signum() && f_high != 0.0 {
+      // Bisection, keeping the half with the sign change
+      for _ in 0..64 {
+        let middle = (low + high) / 2.0;
+        if middle <= low || middle >= high {
+          break;
+        }
+        if f(middle).signum() == f_low.signum() {
+          low = middle;
+        } else {
+          high = middle;
+        }
+      }
+      // Discontinuities like 1 / x also change sign
+      if f(low).abs().min(f(high).abs()) < 1e-2 {
+        roots.push((low + high) / 2.0);
+      } else {
+        jumps.push((low + high) / 2.0);
+      }
+    } else if i > 0 {
+      let previous = samples[i - 1].1;
+      let touches =
+        f_low.abs() < previous.abs() && f_low.abs() < f_high.abs() && f_low.abs() < 1e-3;
+      if touches && previous.signum() == f_low.signum() {
+        roots.push(low);
+      }
+    }
+  }
+  if samples[SAMPLES].1 == 0.0 {
+    roots.push(b);
+  }
+
+  roots.sort_by(|x, y| x.total_cmp(y));
+  roots.dedup_by(|x, y| (*x - *y).abs() < 2.0 * step);
+  // Adding zero turns -0 into 0
+  let snapped = |points: Vec<Prec>| points.into_iter().map(|x| snap(x) + 0.0).collect();
+  Some((snapped(roots), snapped(jumps)))
+}
+
+// Points where the function becomes defined or undefined, like 0 for sqrt(x)
+// Returns the defined side of every edge, found with bisection
+fn domain_edges(f: &dyn Fn(Prec) -> Prec, (a, b): (Prec, Prec)) -> Vec<Prec> {
+  let defined = |x: Prec| !f(x).is_nan();
+  let mut edges = vec![];
+  for i in 0..SAMPLES {
+    let (mut low, mut high) = (sample((a, b), i), sample((a, b), i + 1));
+    let side = defined(low);
+    if side == defined(high) {
+      continue;
+    }
+    for _ in 0..64 {
+      let middle = (low + high) / 2.0;
+      if middle <= low || middle >= high {
+        break;
+      }
+      if defined(middle) == side {
+        low = middle;
+      } else {
+        high = middle;
+      }
+    }
+    edges.push(snap(if side { low } else { high }) + 0.0);
+  }
+  edges
+}
+
+// Points where the function jumps, like 1 for x % 1 and pi / 2 for tan(x)
+// Every sample interval is bisected towards the half where the function changes most, the change shrinks
+// to zero for continuous functions and stays for jumps
+fn discontinuities(f: &dyn Fn(Prec) -> Prec, (a, b): (Prec, Prec)) -> Vec<Prec> {
+  let mut points = vec![];
+  for i in 0..SAMPLES {
+    let (mut low, mut high) = (sample((a, b), i), sample((a, b), i + 1));
+    let (mut f_low, mut f_high) = (f(low), f(high));
+    let change = (f_high - f_low).abs();
+    if change.is_nan() || change == 0.0 {
+      continue;
+    }
+
+    for _ in 0..64 {
+      let middle = (low + high) / 2.0;
+      if middle <= low || middle >= high {
+        break;
+      }
+      let f_middle = f(middle);
+      if (f_middle - f_low).abs() >= (f_high - f_middle).abs() {
+        high = middle;
+        f_high = f_middle;
+      } else {
+        low = middle;
+        f_low = f_middle;
+      }
+    }
+
+    let jump = (f_high - f_low).abs();
+    if jump > 0.1 * change && jump > 1e-4 * f_low.abs().max(f_high.abs()) {
+      points.push(snap((low + high) / 2.0) + 0.0);
+    }
+  }
+  points
+}
+
+// Value the function approaches at point from the side, stepping closer by factors of 10
+// Infinite when the function grows with every step, like tan(x) at pi / 2 where the float closest to the pole is finite
+fn approach(f: &dyn Fn(Prec) -> Prec, point: Prec, side: Prec, step: Prec) -> Option<Prec> {
+  let mut values = vec![];
+  let mut distance = step;
+  loop {
+    let x = point + side * distance;
+    let y = f(x);
+    if x == point || distance == 0.0 || y.is_nan() {
+      break;
+    }
+    values.push(y);
+    distance /= 10.0;
+  }
+
+  let last = *values.last()?;
+  let growing = values.len() > 3
+    && values
+      .windows(2)
+      .rev()
+      .take(3)
+      .all(|w| w[1].abs() > 3.0 * w[0].abs());
+  if growing || last.is_infinite() {
+    Some(last.signum() * Prec::INFINITY)
+  } else {
+    Some(last)
+  }
+}
+
+/// Finds the critical points, inflection points and global extrema of a component on [a, b]
+/// Returns None if the component contains other variables, the interval is not finite or the component is not defined on it
+pub fn extrema(component: &Component, var: &str, (a, b): (Prec, Prec)) -> Option<Extrema> {
+  if !a.is_finite() || !b.is_finite() || a > b {
+    return None;
+  }
+
+  let mut derivatives = Derivatives::new(component);
+  let first = derivatives.get(&[var]);
+  let second = derivatives.get(&[var, var]);
+
+  let f = Compiled::new(component, &[var])?;
+  let f1 = Compiled::new(&first, &[var])?;
+  let f2 = Compiled::new(&second, &[var])?;
+
+  // Sides of a point, used when the second derivative does not decide
+  let width = b / 2.0 - a / 2.0;
+  let h = 2e-3 * width.max(0.5);
+
+  let (stationary, jumps) = roots(&first, var, (a, b))?;
+  let critical_points: Vec<_> = stationary
+    .into_iter()
+    .map(|x| {
+      let curvature = f2.call(&[x]);
+      let classification = if curvature > 1e-4 {
+        Classification::Minimum
+      } else if curvature < -1e-4 {
+        Classification::Maximum
+      } else {
+        // First derivative test
+        match (f1.call(&[x - h]) < 0.0, f1.call(&[x + h]) < 0.0) {
+          (true, false) => Classification::Minimum,
+          (false, true) => Classification::Maximum,
+          _ => Classification::Saddle,
+        }
+      };
+      CriticalPoint {
+        x,
+        value: f.call(&[x]),
+        classification,
+      }
+    })
+    .collect();
+
+  let inflection_points = roots(&second, var, (a, b))?
+    .0
+    .into_iter()
+    .filter(|x| *x > a && *x < b)
+    .filter(|x| f2.call(&[x - h]).signum() != f2.call(&[x + h]).signum())
+    .collect();
+
+  // Global extrema are at a critical point, a point without derivative like abs(x) at 0,
+  // an edge of the domain like 0 for sqrt(x), or an endpoint
+  let candidates = std::iter::once(a)
+    .chain(critical_points.iter().map(|point| point.x))
+    .chain(jumps.iter().copied())
+    .chain(domain_edges(&|x| f.call(&[x]), (a, b)))
+    .chain(std::iter::once(b));
+  // Endpoints keep values which overflow, like x ^ 2 at Prec::MAX
+  let mut values: Vec<_> = candidates
+    .map(|x| (x, f.call(&[x])))
+    .filter(|(_, y)| !y.is_nan())
+    .collect();
+
+  // The function is unbounded near poles, which change the sign of the function like 1 / x,
+  // or of the derivative like 1 / x ^ 2, and approaches values it never takes at jumps like x % 1 at 1,
+  // so the limits from both sides of these points are compared too
+  let call = |x: Prec| f.call(&[x]);
+  let poles = roots(component, var, (a, b))?
+    .1
+    .into_iter()
+    .chain(jumps)
+    .chain(discontinuities(&call, (a, b)));
+  let step = width / SAMPLES as Prec;
+  for pole in poles {
+    // Room between the pole and the end of the interval on each side
+    let sides = [
+      (pole - a, Direction::Left, -1.0),
+      (b - pole, Direction::Right, 1.0),
+    ];
+    for (room, direction, side) in sides.iter() {
+      if *room <= 0.0 {
+        continue;
+      }
+      // A finite limit at the float closest to a pole like pi / 2 is replaced by the infinity it approaches
+      let numeric = approach(&call, pole, *side, step.min(*room));
+      let y = match (numeric, limit::limit(component, var, pole, *direction)) {
+        (Some(y), _) if y.is_infinite() => Some(y),
+        (_, Some(y)) => Some(y),
+        (y, None) => y,
+      };
+      if let Some(y) = y.filter(|y| !y.is_nan()) {
+        values.push((pole, y));
+      }
+    }
+  }
+  let minimum = *values.iter().min_by(|p, q| p.1.total_cmp(&q.1))?;
+  let maximum = *values.iter().max_by(|p, q| p.1.total_cmp(&q.1))?;
+
+  Some(Extrema {
+    critical_points,
+    inflection_points,
+    minimum,
+    maximum,
+  })
+}
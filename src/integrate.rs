@@ -0,0 +1,576 @@
+use std::collections::HashMap;
+
+use super::component::{Component, Prec};
+use super::derive;
+use super::equation::Equation;
+use super::operators::Operator::*;
+use super::polynomial::{snap, Polynomial};
+use super::rational;
+use super::utils::*;
+
+// Maximum nesting of integration by parts and partial fraction decomposition
+const MAX_DEPTH: usize = 4;
+
+// Values of the integration variable at which results are compared, negative values catch sign errors
+const SAMPLES: [Prec; 8] = [-2.6, -1.2, -0.55, 0.35, 0.8, 1.45, 2.3, 3.1];
+
+// Least amount of samples at which both sides need to be finite to be compared
+const MIN_COMPARED: usize = 3;
+
+fn is_var(component: &Component, var: &str) -> bool {
+  match component {
+    Component::Variable(c) => c == var,
+    _ => false,
+  }
+}
+
+// Multiplies two components, merging numbers where possible
+fn mul(left: Component, right: Component) -> Component {
+  match (left.to_float(), right.to_float()) {
+    (Some(a), Some(b)) => Component::Number(a * b),
+    (Some(1.0), _) => right,
+    (_, Some(1.0)) => left,
+    (None, Some(_)) => mul(right, left),
+    (Some(a), None) => {
+      if let Component::Function { operator, values } = &right {
+        if values.len() == 2 {
+          match (operator, values[0].to_float(), values[1].to_float()) {
+            // a * (b * x), becomes (a * b) * x
+            (Multiply, Some(b), _) => return mul(Component::Number(a * b), values[1].clone()),
+            // a * (x / b), becomes (a / b) * x
+            (Divide, _, Some(b)) => return mul(Component::Number(snap(a / b)), values[0].clone()),
+            _ => (),
+          }
+        }
+      }
+
+      match reciprocal_integer(a) {
+        Some(inverse) => {
+          let sign = inverse.signum();
+          create_binary(
+            Divide,
+            mul(Component::Number(sign), right),
+            Component::Number(inverse * sign),
+          )
+        }
+        None => create_binary(Multiply, left, right),
+      }
+    }
+    (None, None) => match right {
+      // x * (a * y), becomes a * (x * y)
+      Component::Function {
+        operator: Multiply,
+        ref values,
+      } if values[0].to_float().is_some() => mul(
+        values[0].clone(),
+        create_binary(Multiply, left, values[1].clone()),
+      ),
+      _ => create_binary(Multiply, left, right),
+    },
+  }
+}
+
+// Subtracts two components, subtracting a negative term becomes an addition
+fn sub(left: Component, right: Component) -> Component {
+  if let Component::Function {
+    operator: Multiply,
+    values,
+  } = &right
+  {
+    if let Some(f) = values[0].to_float() {
+      if f < 0.0 {
+        return create_binary(Add, left, mul(Component::Number(-f), values[1].clone()));
+      }
+    }
+  }
+  create_binary(Subtract, left, right)
+}
+
+fn div(left: Component, right: Component) -> Component {
+  match right.to_float() {
+    Some(1.0) => left,
+    Some(-1.0) => mul(Component::Number(-1.0), left),
+    _ => create_binary(Divide, left, right),
+  }
+}
+
+fn neg(component: Component) -> Component {
+  mul(Component::Number(-1.0), component)
+}
+
+fn product(factors: &[Component]) -> Component {
+  factors.iter().cloned().fold(Component::Number(1.0), mul)
+}
+
+// Splits a product into its factors, divisors become negative powers
+fn collect_factors(component: &Component, factors: &mut Vec<Component>) {
+  match component {
+    Component::Function {
+      operator: Multiply,
+      values,
+    } => {
+      collect_factors(&values[0], factors);
+      collect_factors(&values[1], factors);
+    }
+    Component::Function {
+      operator: Divide,
+      values,
+    } => {
+      collect_factors(&values[0], factors);
+      let mut divisors = vec![];
+      collect_factors(&values[1], &mut divisors);
+      for (base, power) in divisors.iter().map(base_power) {
+        factors.push(match base.to_float() {
+          Some(f) => Component::Number(f.powf(-power)),
+          None => create_binary(Exponent, base, Component::Number(-power)),
+        });
+      }
+    }
+    _ => factors.push(component.clone()),
+  }
+}
+
+// Splits a factor into its base and numeric power
+fn base_power(factor: &Component) -> (Component, Prec) {
+  if let Component::Function {
+    operator: Exponent,
+    values,
+  } = factor
+  {
+    if let Some(power) = values[1].to_float() {
+      return (values[0].clone(), power);
+    }
+  }
+  (factor.clone(), 1.0)
+}
+
+// Factors of a product, with the powers of equal bases added together
+fn factors(component: &Component) -> Vec<Component> {
+  let mut all = vec![];
+  collect_factors(component, &mut all);
+
+  let mut combined: Vec<(Component, Prec)> = vec![];
+  for (base, power) in all.iter().map(base_power) {
+    match combined
+      .iter_mut()
+      .find(|(b, _)| b.to_string() == base.to_string())
+    {
+      Some((_, p)) => *p += power,
+      None => combined.push((base, power)),
+    }
+  }
+
+  combined
+    .into_iter()
+    .filter(|(_, power)| *power != 0.0)
+    .map(|(base, power)| {
+      if power == 1.0 {
+        base
+      } else {
+        create_binary(Exponent, base, Component::Number(power))
+      }
+    })
+    .collect()
+}
+
+// Evaluates a component at the given sample of the integration variable
+// Other variables get fixed values which differ per variable
+fn sample(component: &Component, var: &str, vars: &[String], i: usize) -> Option<Prec> {
+  let values: HashMap<&str, Prec> = vars
+    .iter()
+    .enumerate()
+    .map(|(j, v)| {
+      if v == var {
+        (v.as_str(), SAMPLES[i])
+      } else {
+        (v.as_str(), 0.5 + 0.37 * (j + 1) as Prec + 0.11 * i as Prec)
+      }
+    })
+    .collect();
+
+  Equation::solve_component(&values, component)
+    .to_float()
+    .filter(|f| f.is_finite())
+}
+
+fn approx_eq(a: Prec, b: Prec) -> bool {
+  (a - b).abs() <= 1e-4 * a.abs().max(b.abs()).max(1.0)
+}
+
+// Whether enough samples were compared, most of the samples at which either side is finite are required
+fn enough(compared: usize, defined: usize) -> bool {
+  compared >= MIN_COMPARED && compared * 4 >= defined * 3
+}
+
+// Checks numerically if two components are equal
+fn equal(a: &Component, b: &Component, var: &str) -> bool {
+  let vars = create_binary(Add, a.clone(), b.clone()).variables();
+  let (mut compared, mut defined) = (0, 0);
+
+  for i in 0..SAMPLES.len() {
+    match (sample(a, var, &vars, i), sample(b, var, &vars, i)) {
+      (Some(x), Some(y)) if !approx_eq(x, y) => return false,
+      (Some(_), Some(_)) => compared += 1,
+      (None, None) => continue,
+      _ => (),
+    }
+    defined += 1;
+  }
+  enough(compared, defined)
+}
+
+// Returns the component without var if it does not change when var changes
+fn constant_in(component: &Component, var: &str) -> Option<Component> {
+  let vars = component.variables();
+  let values: Vec<_> = (0..SAMPLES.len())
+    .filter_map(|i| sample(component, var, &vars, i).map(|f| (i, f)))
+    .collect();
+  if values.len() < MIN_COMPARED || values.iter().any(|(_, f)| !approx_eq(*f, values[0].1)) {
+    return None;
+  }
+
+  let (i, f) = values[0];
+  if vars.iter().all(|v| v == var) {
+    Some(Component::Number(snap(f)))
+  } else {
+    Some(clean(&component.substitute(
+      &Component::Variable(var.to_string()),
+      &Component::Number(SAMPLES[i]),
+    )))
+  }
+}
+
+// Whether the component contains floor, round, ceil or % of var, which jump at every step
+// Their antiderivatives are not continuous, so the derivative check would accept wrong results
+fn has_steps(component: &Component, var: &str) -> bool {
+  match component {
+    Component::Function { operator, values } => match operator {
+      Modulo | Floor | Round | Ceil if depends_on(component, var) => true,
+      _ => values.iter().any(|value| has_steps(value, var)),
+    },
+    _ => false,
+  }
+}
+
+// Folds constants like clean, logarithms of numbers like ln(2) are kept instead of becoming a rounded number
+fn fold(component: &Component) -> Component {
+  // The placeholders are named like the logarithm, a name with parentheses can't occur in a parsed equation
+  fn protect(component: &Component, logs: &mut Vec<Component>) -> Component {
+    match component {
+      Component::Function {
+        operator: Ln,
+        values,
+      } if values[0].to_float().is_some() => {
+        logs.push(component.clone());
+        Component::Variable(component.to_string())
+      }
+      Component::Function { operator, values } => Component::Function {
+        operator: operator.clone(),
+        values: values.iter().map(|value| protect(value, logs)).collect(),
+      },
+      _ => component.clone(),
+    }
+  }
+
+  let mut logs = vec![];
+  let folded = clean(&protect(component, &mut logs));
+  logs.iter().fold(folded, |folded, log| {
+    folded.substitute(&Component::Variable(log.to_string()), log)
+  })
+}
+
+// Name for a substituted variable which is not used in the component
+fn fresh_var(component: &Component) -> String {
+  let vars = component.variables();
+  (0..)
+    .map(|i| format!("u{}", i))
+    .find(|name| !vars.contains(name))
+    .unwrap()
+}
+
+// Antiderivatives of the standard functions of a single variable
+fn table(factors: &[Component], var: &str) -> Option<Component> {
+  let x = Component::Variable(var.to_string());
+  let unary = |operator| create_unary(operator, x.clone());
+
+  if factors.len() == 2 {
+    // sec(x) * tan(x) and csc(x) * cot(x)
+    for (a, b) in [(&factors[0], &factors[1]), (&factors[1], &factors[0])].iter() {
+      if let (
+        Component::Function {
+          operator: first,
+          values: first_values,
+        },
+        Component::Function {
+          operator: second,
+          values: second_values,
+        },
+      ) = (a, b)
+      {
+        if !is_var(&first_values[0], var) || !is_var(&second_values[0], var) {
+          continue;
+        }
+        match (first, second) {
+          (Sec, Tan) => return Some(unary(Sec)),
+          (Csc, Cot) => return Some(neg(unary(Csc))),
+          _ => (),
+        }
+      }
+    }
+    return None;
+  }
+
+  if factors.len() != 1 {
+    return None;
+  }
+
+  match &factors[0] {
+    Component::Variable(c) if c == var => Some(div(
+      create_binary(Exponent, x, Component::Number(2.0)),
+      Component::Number(2.0),
+    )),
+
+    Component::Function { operator, values } if values.len() == 1 => {
+      if !is_var(&values[0], var) {
+        return None;
+      }
+
+      let abs_ln = |c| create_unary(Ln, create_unary(Abs, c));
+      Some(match operator {
+        Exp => unary(Exp),
+        Ln => create_binary(Subtract, mul(x.clone(), unary(Ln)), x),
+        Sin => neg(unary(Cos)),
+        Cos => unary(Sin),
+        Tan => neg(abs_ln(unary(Cos))),
+        Sec => abs_ln(create_binary(Add, unary(Sec), unary(Tan))),
+        Csc => neg(abs_ln(create_binary(Add, unary(Csc), unary(Cot)))),
+        Cot => abs_ln(unary(Sin)),
+        Sqrt => div(
+          mul(
+            Component::Number(2.0),
+            create_binary(Exponent, x, Component::Number(1.5)),
+          ),
+          Component::Number(3.0),
+        ),
+        _ => return None,
+      })
+    }
+
+    Component::Function { operator, values } => {
+      let (base, power) = (&values[0], &values[1]);
+
+      match operator {
+        Exponent | Pow if is_var(base, var) => match power.to_float() {
+          // x ^ -1 equals 1 / x
+          Some(-1.0) => Some(create_unary(Ln, create_unary(Abs, x))),
+          Some(n) => Some(div(
+            create_binary(Exponent, x, Component::Number(n + 1.0)),
+            Component::Number(n + 1.0),
+          )),
+          None => None,
+        },
+
+        // a ^ x
+        Exponent | Pow if is_var(power, var) && !depends_on(base, var) => {
+          Some(div(factors[0].clone(), create_unary(Ln, base.clone())))
+        }
+
+        // sec(x) ^ 2 and csc(x) ^ 2
+        Exponent | Pow if power.to_float() == Some(2.0) => match base {
+          Component::Function { operator, values } if is_var(&values[0], var) => match operator {
+            Sec => Some(unary(Tan)),
+            Csc => Some(neg(unary(Cot))),
+            _ => None,
+          },
+          _ => None,
+        },
+
+        Log if is_var(base, var) && !depends_on(power, var) => Some(div(
+          create_binary(Subtract, mul(x.clone(), unary(Ln)), x),
+          create_unary(Ln, power.clone()),
+        )),
+
+        Root if is_var(base, var) => {
+          let n = 1.0 / power.to_float()? + 1.0;
+          Some(div(
+            create_binary(Exponent, x, Component::Number(n)),
+            Component::Number(n),
+          ))
+        }
+
+        _ => None,
+      }
+    }
+
+    _ => None,
+  }
+}
+
+// Inner components g of a factor f(g), which are candidates for substitution
+fn inner_components(factor: &Component, var: &str) -> Vec<Component> {
+  let candidates = match factor {
+    Component::Function { operator, values } => match operator {
+      Exponent | Pow if !depends_on(&values[1], var) => vec![values[0].clone()],
+      Exponent | Pow if !depends_on(&values[0], var) => vec![values[1].clone()],
+      Log | Root => vec![values[0].clone()],
+      _ if values.len() == 1 => vec![values[0].clone()],
+      _ => vec![],
+    },
+    _ => vec![],
+  };
+
+  // The factor itself is also a candidate, for f(x) * f'(x)
+  let candidates = candidates.into_iter().chain(match factor {
+    Component::Function { .. } => Some(factor.clone()),
+    _ => None,
+  });
+
+  candidates
+    .into_iter()
+    .filter(|g| depends_on(g, var) && !is_var(g, var))
+    .collect()
+}
+
+// Integrates f(g(x)) * g'(x) * c by substituting u = g(x)
+fn substitution(factors: &[Component], var: &str, depth: usize) -> Option<Component> {
+  for (i, factor) in factors.iter().enumerate() {
+    for inner in inner_components(factor, var) {
+      let rest: Vec<_> = factors
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != i)
+        .map(|(_, f)| f.clone())
+        .collect();
+
+      let derivative = clean(&derive::derive_partial(&inner, var));
+      let ratio = match constant_in(&div(product(&rest), derivative), var) {
+        Some(ratio) => ratio,
+        None => continue,
+      };
+
+      let u = Component::Variable(fresh_var(factor));
+      let substituted = factor.substitute(&inner, &u);
+      if depends_on(&substituted, var) {
+        continue;
+      }
+
+      if let Some(integral) = integrate(&substituted, &u.to_string(), depth) {
+        return Some(mul(ratio, integral.substitute(&u, &inner)));
+      }
+    }
+  }
+  None
+}
+
+// Expands polynomials and decomposes rational functions into partial fractions
+fn expand(component: &Component, var: &str, depth: usize) -> Option<Component> {
+  if depth >= MAX_DEPTH {
+    return None;
+  }
+
+  let expanded = match Polynomial::<Prec>::from_component(component) {
+    Some(p) => p.to_component(),
+    None => rational::apart(component, var),
+  };
+
+  if expanded.to_string() == component.to_string() {
+    None
+  } else {
+    integrate(&expanded, var, depth + 1)
+  }
+}
+
+// Order in which factors are chosen to be derived in integration by parts
+// Logarithms, then powers of the variable, then trigonometry and exponents last
+fn parts_priority(factor: &Component, var: &str) -> Option<u8> {
+  match factor {
+    Component::Variable(c) if c == var => Some(1),
+    Component::Function { operator, values } => match operator {
+      Ln | Log => Some(0),
+      Exponent | Pow if is_var(&values[0], var) => match values[1].to_float() {
+        Some(n) if n > 0.0 && n.fract() == 0.0 => Some(1),
+        _ => None,
+      },
+      Sin | Cos | Tan | Sec | Csc | Cot => Some(2),
+      Exponent | Pow | Exp => Some(3),
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+// Integration by parts, the integral of u * v' equals u * v minus the integral of u' * v
+fn by_parts(factors: &[Component], var: &str, depth: usize) -> Option<Component> {
+  if depth >= MAX_DEPTH || factors.len() < 2 {
+    return None;
+  }
+
+  let (i, _) = factors
+    .iter()
+    .enumerate()
+    .filter_map(|(i, f)| parts_priority(f, var).map(|p| (i, p)))
+    .min_by_key(|(_, p)| *p)?;
+
+  let u = factors[i].clone();
+  let rest: Vec<_> = factors
+    .iter()
+    .enumerate()
+    .filter(|(j, _)| *j != i)
+    .map(|(_, f)| f.clone())
+    .collect();
+
+  let v = integrate(&product(&rest), var, depth + 1)?;
+  let derivative = clean(&derive::derive_partial(&u, var));
+  let remaining = integrate(&fold(&mul(v.clone(), derivative)), var, depth + 1)?;
+
+  Some(sub(mul(u, v), remaining))
+}
+
+fn integrate(component: &Component, var: &str, depth: usize) -> Option<Component> {
+  if !depends_on(component, var) {
+    return Some(mul(component.clone(), Component::Variable(var.to_string())));
+  }
+
+  // Linearity
+  if let Component::Function { operator, values } = component {
+    if let Add | Subtract = operator {
+      return Some(create_binary(
+        operator.clone(),
+        integrate(&values[0], var, depth)?,
+        integrate(&values[1], var, depth)?,
+      ));
+    }
+  }
+
+  // Constant factors are moved out of the integral
+  let (constant, dependent): (Vec<_>, Vec<_>) = factors(component)
+    .into_iter()
+    .partition(|f| !depends_on(f, var));
+  if !constant.is_empty() {
+    let constant = product(&constant);
+    return integrate(&product(&dependent), var, depth).map(|f| mul(constant, f));
+  }
+
+  table(&dependent, var)
+    .or_else(|| substitution(&dependent, var, depth))
+    .or_else(|| expand(component, var, depth))
+    .or_else(|| by_parts(&dependent, var, depth))
+}
+
+/// Finds an antiderivative of a component with respect to var
+///
+/// The result is verified by deriving it and comparing it to the original component,
+/// returns None if no antiderivative was found or the component contains step functions like floor(x).
+pub fn integrate_component(component: &Component, var: &str) -> Option<Component> {
+  if has_steps(component, var) {
+    return None;
+  }
+
+  let integral = fold(&integrate(component, var, 0)?);
+
+  if equal(&derive::derive_partial(&integral, var), component, var) {
+    Some(integral)
+  } else {
+    None
+  }
+}
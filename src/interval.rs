@@ -0,0 +1,236 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use super::component::{Component, Prec};
+use super::operators::Operator::*;
+use super::summation;
+
+// Amount of pieces the range may be split into while the bound is refined
+const MAX_PIECES: usize = 4096;
+
+// Pieces are split until their bound is within this fraction of the largest value found
+const TOLERANCE: f64 = 1e-3;
+
+// Relative widening of the result, which covers the rounding of the double precision operations
+const ROUNDING: f64 = 1e-9;
+
+// A range which contains every value a component takes while its variable moves through a range
+// Computed in double precision, so rounding stays far below the precision of Prec
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Interval {
+  low: f64,
+  high: f64,
+}
+
+impl Interval {
+  fn point(f: f64) -> Interval {
+    Interval { low: f, high: f }
+  }
+
+  // Smallest interval containing all values, None if one of them is NaN
+  fn hull(values: &[f64]) -> Option<Interval> {
+    if values.iter().any(|f| f.is_nan()) {
+      return None;
+    }
+    Some(Interval {
+      low: values.iter().cloned().fold(f64::INFINITY, f64::min),
+      high: values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+    })
+  }
+
+  fn contains(&self, f: f64) -> bool {
+    self.low <= f && f <= self.high
+  }
+
+  fn magnitude(&self) -> f64 {
+    self.low.abs().max(self.high.abs())
+  }
+
+  fn multiply(&self, other: &Interval) -> Option<Interval> {
+    Interval::hull(&[
+      self.low * other.low,
+      self.low * other.high,
+      self.high * other.low,
+      self.high * other.high,
+    ])
+  }
+
+  // Not bounded when the interval contains zero
+  fn inverse(&self) -> Option<Interval> {
+    if self.contains(0.0) {
+      None
+    } else {
+      Interval::hull(&[1.0 / self.low, 1.0 / self.high])
+    }
+  }
+
+  fn power(&self, exponent: &Interval) -> Option<Interval> {
+    if exponent.low == exponent.high && exponent.low.fract() == 0.0 {
+      let n = exponent.low;
+      return if n < 0.0 {
+        self.power(&Interval::point(-n))?.inverse()
+      } else if n % 2.0 == 0.0 && self.contains(0.0) {
+        Interval::hull(&[0.0, self.magnitude().powf(n)])
+      } else {
+        Interval::hull(&[self.low.powf(n), self.high.powf(n)])
+      };
+    }
+
+    // Non integer powers are only defined for positive bases, and monotone in both the base and the exponent
+    if self.low < 0.0 {
+      return None;
+    }
+    Interval::hull(&[
+      self.low.powf(exponent.low),
+      self.low.powf(exponent.high),
+      self.high.powf(exponent.low),
+      self.high.powf(exponent.high),
+    ])
+  }
+
+  fn sin(&self) -> Option<Interval> {
+    if self.high - self.low >= 2.0 * PI {
+      return Some(Interval {
+        low: -1.0,
+        high: 1.0,
+      });
+    }
+
+    // The first maximum at pi / 2 + 2 * k * pi and minimum at -pi / 2 + 2 * k * pi past the lower end
+    let next = |offset: f64| offset + 2.0 * PI * ((self.low - offset) / (2.0 * PI)).ceil();
+    let range = Interval::hull(&[self.low.sin(), self.high.sin()])?;
+    Some(Interval {
+      low: if next(-FRAC_PI_2) <= self.high {
+        -1.0
+      } else {
+        range.low
+      },
+      high: if next(FRAC_PI_2) <= self.high {
+        1.0
+      } else {
+        range.high
+      },
+    })
+  }
+
+  // Monotone on the branches between its poles at offset + k * pi
+  fn branch(&self, offset: f64, f: fn(f64) -> f64) -> Option<Interval> {
+    let pole = offset + PI * ((self.low - offset) / PI).ceil();
+    if pole <= self.high {
+      None
+    } else {
+      Interval::hull(&[f(self.low), f(self.high)])
+    }
+  }
+
+  fn monotone(&self, f: fn(f64) -> f64) -> Option<Interval> {
+    Interval::hull(&[f(self.low), f(self.high)])
+  }
+}
+
+fn evaluate(component: &Component, var: &str, range: Interval) -> Option<Interval> {
+  let (operator, values) = match component {
+    Component::Number(f) => return Some(Interval::point(f64::from(*f))),
+    Component::Variable(c) if c == var => return Some(range),
+    Component::Function { operator, values } => (operator, values),
+    _ => return None,
+  };
+  let values = values
+    .iter()
+    .map(|x| evaluate(x, var, range))
+    .collect::<Option<Vec<_>>>()?;
+
+  match (operator, values.as_slice()) {
+    (Add, [a, b]) => Interval::hull(&[a.low + b.low, a.high + b.high]),
+    (Subtract, [a, b]) => Interval::hull(&[a.low - b.high, a.high - b.low]),
+    (Multiply, [a, b]) => a.multiply(b),
+    (Divide, [a, b]) => a.multiply(&b.inverse()?),
+    // The remainder takes the sign of a and is smaller than both a and b
+    (Modulo, [a, b]) => {
+      let magnitude = a.magnitude().min(b.magnitude());
+      Interval::hull(&[
+        if a.low < 0.0 { -magnitude } else { 0.0 },
+        if a.high > 0.0 { magnitude } else { 0.0 },
+      ])
+    }
+    (Exponent, [a, b]) | (Pow, [a, b]) => a.power(b),
+    (Root, [a, b]) => a.power(&Interval::point(1.0).multiply(&b.inverse()?)?),
+    (Log, [a, b]) => log(a)?.multiply(&log(b)?.inverse()?),
+    (Sin, [a]) => a.sin(),
+    (Cos, [a]) => Interval::hull(&[a.low + FRAC_PI_2, a.high + FRAC_PI_2])?.sin(),
+    (Tan, [a]) => a.branch(FRAC_PI_2, f64::tan),
+    (Sec, [a]) => Interval::hull(&[a.low + FRAC_PI_2, a.high + FRAC_PI_2])?
+      .sin()?
+      .inverse(),
+    (Csc, [a]) => a.sin()?.inverse(),
+    (Cot, [a]) => a.branch(0.0, |f| 1.0 / f.tan()),
+    (Abs, [a]) if a.contains(0.0) => Interval::hull(&[0.0, a.magnitude()]),
+    (Abs, [a]) => a.monotone(f64::abs),
+    (Floor, [a]) => a.monotone(f64::floor),
+    (Round, [a]) => a.monotone(f64::round),
+    (Ceil, [a]) => a.monotone(f64::ceil),
+    (Exp, [a]) => a.monotone(f64::exp),
+    (Ln, [a]) => log(a),
+    (Sqrt, [a]) if a.low >= 0.0 => a.monotone(f64::sqrt),
+    _ => None,
+  }
+}
+
+fn log(a: &Interval) -> Option<Interval> {
+  if a.low > 0.0 {
+    a.monotone(f64::ln)
+  } else {
+    None
+  }
+}
+
+// Bounds |component| from above while var moves between a and b, with branch and bound
+// Pieces are split while their interval bound is far above the largest value found at their midpoints,
+// so the bound is guaranteed and close to the actual maximum
+// Returns None if the component contains other variables or is not bounded on the range
+pub(crate) fn bound(component: &Component, var: &str, a: Prec, b: Prec) -> Option<f64> {
+  let component = summation::unroll(component)?;
+  let magnitude = |low: f64, high: f64| {
+    evaluate(&component, var, Interval { low, high })
+      .map(|range| range.magnitude())
+      .filter(|f| f.is_finite())
+  };
+
+  let mut found: f64 = 0.0;
+  let mut bound: f64 = 0.0;
+  let mut evaluated = 0;
+  let mut pieces = vec![(f64::from(a.min(b)), f64::from(a.max(b)))];
+  while let Some((low, high)) = pieces.pop() {
+    evaluated += 1;
+    let middle = (low + high) / 2.0;
+    if let Some(f) = magnitude(middle, middle) {
+      found = found.max(f);
+    }
+
+    let done = evaluated >= MAX_PIECES || middle <= low || middle >= high;
+    match magnitude(low, high) {
+      Some(f) if done || f <= found * (1.0 + TOLERANCE) => bound = bound.max(f),
+      None if done => return None,
+      _ => {
+        pieces.push((low, middle));
+        pieces.push((middle, high));
+      }
+    }
+  }
+  Some(bound * (1.0 + ROUNDING))
+}
+
+// Rounds up to the next Prec, so a bound stays a bound
+pub(crate) fn round_up(f: f64) -> Prec {
+  let rounded = f as Prec;
+  if f64::from(rounded) < f && rounded.is_finite() {
+    Prec::from_bits(if rounded > 0.0 {
+      rounded.to_bits() + 1
+    } else if rounded < 0.0 {
+      rounded.to_bits() - 1
+    } else {
+      1
+    })
+  } else {
+    rounded
+  }
+}
@@ -0,0 +1,597 @@
+use super::component::{Component, Prec};
+use super::operators::{Operator, Operator::*};
+
+// Greek letters which are written as a command when used as a variable name
+pub(crate) const GREEK: [&str; 33] = [
+  "alpha",
+  "beta",
+  "gamma",
+  "delta",
+  "epsilon",
+  "varepsilon",
+  "zeta",
+  "eta",
+  "theta",
+  "vartheta",
+  "iota",
+  "kappa",
+  "lambda",
+  "mu",
+  "nu",
+  "xi",
+  "pi",
+  "rho",
+  "sigma",
+  "tau",
+  "upsilon",
+  "phi",
+  "varphi",
+  "chi",
+  "psi",
+  "omega",
+  "Gamma",
+  "Delta",
+  "Theta",
+  "Lambda",
+  "Sigma",
+  "Phi",
+  "Omega",
+];
+
+// Binding strength of a component, atoms like fractions and functions never need parentheses
+fn precedence(component: &Component) -> i8 {
+  match component {
+    Component::Number(f) if *f < 0.0 => 0,
+    Component::Function { operator, values } => match operator {
+      Add | Subtract => 1,
+      Sum | Product => 2,
+      Multiply | Modulo => 3,
+      Exponent | Pow | Exp if values.len() <= 2 => 5,
+      _ => 6,
+    },
+    _ => 6,
+  }
+}
+
+fn parenthesize(text: String) -> String {
+  format!("\\left({}\\right)", text)
+}
+
+/// Checks if a value of an operator needs parentheses in typeset math, where fractions and powers are boxes
+/// A leading minus sign reads the same as a negative left operand, so those need no parentheses
+pub(crate) fn parenthesized(operator: &Operator, index: usize, value: &Component) -> bool {
+  let prec = match value {
+    Component::Number(f) if *f < 0.0 && index == 0 => 3,
+    _ => precedence(value),
+  };
+  match (operator, index) {
+    (Add, _) | (Subtract, 0) => prec < 1,
+    // a - (b + c) or a - (b - c) changes the sign of c
+    (Subtract, _) => prec < 2,
+    (Multiply, 0) | (Modulo, 0) => prec < 3,
+    (Multiply, _) => {
+      prec < 3
+        || matches!(
+          value,
+          Component::Function {
+            operator: Modulo,
+            ..
+          }
+        )
+    }
+    (Modulo, _) => prec < 4,
+    // Anything but an atom would be read as only partly raised
+    (Exponent, 0) | (Pow, 0) => {
+      prec < 6
+        || matches!(
+          value,
+          Component::Function {
+            operator: Divide,
+            ..
+          }
+        )
+    }
+    (Sum, 0) | (Product, 0) => prec < 3,
+    _ => false,
+  }
+}
+
+fn variable(name: &str) -> String {
+  // Everything after the first underscore is a subscript
+  if let Some(i) = name.find('_') {
+    return format!("{}_{{{}}}", variable(&name[..i]), &name[i + 1..]);
+  }
+  if GREEK.contains(&name) {
+    format!("\\{}", name)
+  } else if name.chars().count() > 1 {
+    format!("\\mathrm{{{}}}", name)
+  } else {
+    name.to_string()
+  }
+}
+
+fn number(f: Prec) -> String {
+  if f.is_nan() {
+    String::from("\\mathrm{NaN}")
+  } else if f.is_infinite() {
+    format!("{}\\infty", if f < 0.0 { "-" } else { "" })
+  } else {
+    f.to_string()
+  }
+}
+
+/// Converts a component to LaTeX, with as few braces and parentheses as possible
+pub fn to_latex(component: &Component) -> String {
+  let (operator, values) = match component {
+    Component::Variable(c) => return variable(c),
+    Component::Number(f) => return number(*f),
+    Component::Function { operator, values } => (operator, values),
+    Component::End => return String::new(),
+  };
+
+  let function = |name: &str| {
+    let arguments: Vec<_> = values.iter().map(to_latex).collect();
+    format!("{}\\left({}\\right)", name, arguments.join(", "))
+  };
+  let operand = |i: usize| {
+    let text = to_latex(&values[i]);
+    if parenthesized(operator, i, &values[i]) {
+      parenthesize(text)
+    } else {
+      text
+    }
+  };
+
+  match (operator, values.as_slice()) {
+    (Add, [_, _]) => format!("{} + {}", operand(0), operand(1)),
+    (Subtract, [_, _]) => format!("{} - {}", operand(0), operand(1)),
+    (Multiply, [_, _]) => format!("{} \\cdot {}", operand(0), operand(1)),
+    (Modulo, [_, _]) => format!("{} \\bmod {}", operand(0), operand(1)),
+    (Divide, [a, b]) => format!("\\frac{{{}}}{{{}}}", to_latex(a), to_latex(b)),
+    (Exponent, [_, b]) | (Pow, [_, b]) => format!("{}^{{{}}}", operand(0), to_latex(b)),
+    (Exp, [a]) => format!("e^{{{}}}", to_latex(a)),
+    (Sqrt, [a]) => format!("\\sqrt{{{}}}", to_latex(a)),
+    (Root, [a, b]) => format!("\\sqrt[{}]{{{}}}", to_latex(b), to_latex(a)),
+    (Log, [a, b]) => format!("\\log_{{{}}}\\left({}\\right)", to_latex(b), to_latex(a)),
+    (Abs, [a]) => format!("\\left|{}\\right|", to_latex(a)),
+    (Floor, [a]) => format!("\\left\\lfloor {} \\right\\rfloor", to_latex(a)),
+    (Ceil, [a]) => format!("\\left\\lceil {} \\right\\rceil", to_latex(a)),
+    (Sum, [_, k, from, to]) | (Product, [_, k, from, to]) => format!(
+      "\\{}_{{{}={}}}^{{{}}} {}",
+      if operator.compare(&Sum) {
+        "sum"
+      } else {
+        "prod"
+      },
+      to_latex(k),
+      to_latex(from),
+      to_latex(to),
+      operand(0)
+    ),
+    (Ln, _) | (Sin, _) | (Cos, _) | (Tan, _) | (Sec, _) | (Csc, _) | (Cot, _) => {
+      function(&format!("\\{}", operator))
+    }
+    _ => function(&format!("\\operatorname{{{}}}", operator)),
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Number(String),
+  Letter(char),
+  Command(String),
+  Symbol(char),
+}
+
+// Commands which only add space
+const SPACES: [&str; 7] = [",", ";", ":", "!", " ", "quad", "qquad"];
+
+fn tokenize(latex: &str) -> Vec<Token> {
+  let mut chars = latex.chars().peekable();
+  let mut tokens = vec![];
+  while let Some(c) = chars.next() {
+    if c == '\\' {
+      let mut name = String::new();
+      while let Some(c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+        name.push(*c);
+        chars.next();
+      }
+      // Commands like \, and \{ are a single character
+      if name.is_empty() {
+        name.extend(chars.next());
+      }
+      if !SPACES.contains(&name.as_str()) {
+        tokens.push(Token::Command(name));
+      }
+    } else if c.is_ascii_digit() || c == '.' {
+      let mut number = c.to_string();
+      while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+        number.push(*c);
+        chars.next();
+      }
+      tokens.push(Token::Number(number));
+    } else if c.is_alphabetic() {
+      tokens.push(Token::Letter(c));
+    } else if !c.is_whitespace() {
+      tokens.push(Token::Symbol(c));
+    }
+  }
+  tokens
+}
+
+// Infix text with the precedence of its weakest operator outside of parentheses
+struct Infix {
+  text: String,
+  prec: i8,
+}
+
+impl Infix {
+  fn atom(text: String) -> Infix {
+    Infix { text, prec: 6 }
+  }
+
+  // Text which can be used as an operand of an operator with the given precedence
+  fn operand(&self, prec: i8) -> String {
+    if self.prec < prec {
+      format!("({})", self.text)
+    } else {
+      self.text.clone()
+    }
+  }
+}
+
+enum Item {
+  Operator(char),
+  Infix(Infix),
+}
+
+fn precedence_of(operator: char) -> i8 {
+  match operator {
+    '+' | '-' => 1,
+    _ => 3,
+  }
+}
+
+// Recursive descent over the tokens, every method returns None for unsupported or malformed input
+struct Reader {
+  tokens: Vec<Token>,
+  index: usize,
+}
+
+impl Reader {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.index)
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.index).cloned();
+    self.index += 1;
+    token
+  }
+
+  fn eat(&mut self, token: Token) -> bool {
+    if self.peek() == Some(&token) {
+      self.index += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn expect(&mut self, token: Token) -> Option<()> {
+    if self.eat(token) {
+      Some(())
+    } else {
+      None
+    }
+  }
+
+  // Closes a group opened with (, [, | or \left
+  fn close(&mut self, delimiter: Token) -> Option<()> {
+    self.eat(Token::Command(String::from("right")));
+    self.expect(delimiter)
+  }
+
+  fn closing(&self) -> bool {
+    match self.peek() {
+      None => true,
+      Some(Token::Symbol(c)) => matches!(c, ')' | ']' | '}' | '|' | '='),
+      Some(Token::Command(c)) => matches!(c.as_str(), "right" | "rfloor" | "rceil" | "}"),
+      _ => false,
+    }
+  }
+
+  // Reads operators and operands until a closing delimiter, a term stops at + and - as well
+  fn expression(&mut self, term: bool) -> Option<Infix> {
+    let mut items = vec![];
+    while !self.closing() {
+      let operator = match self.peek()? {
+        Token::Symbol(c) if matches!(c, '+' | '-' | '*' | '/') => Some(*c),
+        Token::Command(c) => match c.as_str() {
+          "cdot" | "times" | "ast" => Some('*'),
+          "div" => Some('/'),
+          "bmod" | "mod" => Some('%'),
+          _ => None,
+        },
+        _ => None,
+      };
+
+      match operator {
+        Some('+') | Some('-') if term && !items.is_empty() => break,
+        Some(operator) => {
+          self.index += 1;
+          items.push(Item::Operator(operator));
+        }
+        None => {
+          // Operands next to each other are multiplied, binding tighter than the operators, so 2\pi stays together after \bmod
+          let atom = self.atom()?;
+          match items.pop() {
+            Some(Item::Infix(left)) => items.push(Item::Infix(Infix {
+              text: format!("{} * {}", left.operand(3), atom.operand(4)),
+              prec: 3,
+            })),
+            last => {
+              items.extend(last);
+              items.push(Item::Infix(atom));
+            }
+          }
+        }
+      }
+    }
+    join(&items)
+  }
+
+  // A group in braces or a single token, like the 2 in x^2 or x^23
+  fn group(&mut self) -> Option<Infix> {
+    match self.peek()? {
+      Token::Symbol('{') => {
+        self.index += 1;
+        let inner = self.expression(false)?;
+        self.expect(Token::Symbol('}'))?;
+        Some(inner)
+      }
+      Token::Number(n) if n.len() > 1 => {
+        let n = n.clone();
+        self.tokens[self.index] = Token::Number(n[1..].to_string());
+        Some(Infix::atom(n[..1].to_string()))
+      }
+      _ => self.primary(),
+    }
+  }
+
+  // The text of a group without translating it, used for subscripts and names
+  fn raw(&mut self) -> Option<String> {
+    let tokens = match self.next()? {
+      Token::Symbol('{') => {
+        let start = self.index;
+        while self.peek()? != &Token::Symbol('}') {
+          self.index += 1;
+        }
+        self.index += 1;
+        self.tokens[start..self.index - 1].to_vec()
+      }
+      token => vec![token],
+    };
+    tokens
+      .into_iter()
+      .map(|token| match token {
+        Token::Number(n) => Some(n),
+        Token::Letter(c) => Some(c.to_string()),
+        Token::Command(c) if GREEK.contains(&c.as_str()) => Some(c),
+        _ => None,
+      })
+      .collect()
+  }
+
+  // A variable name with an optional subscript
+  fn variable(&mut self, name: String) -> Option<Infix> {
+    if self.eat(Token::Symbol('_')) {
+      return Some(Infix::atom(format!("{}_{}", name, self.raw()?)));
+    }
+    Some(Infix::atom(name))
+  }
+
+  // The argument of a function like \sin, parentheses are optional
+  fn argument(&mut self) -> Option<Infix> {
+    if self.parenthesized() {
+      self.primary()
+    } else if self.peek()? == &Token::Symbol('{') {
+      self.group()
+    } else {
+      self.atom()
+    }
+  }
+
+  fn parenthesized(&self) -> bool {
+    match self.peek() {
+      Some(Token::Symbol(c)) => matches!(c, '(' | '['),
+      Some(Token::Command(c)) => c == "left",
+      _ => false,
+    }
+  }
+
+  // Applies a function to its argument and the extra parameters, sin^2 x is written as sin(x) ^ 2
+  fn function(&mut self, name: &str, parameters: &[String]) -> Option<Infix> {
+    let power = if self.eat(Token::Symbol('^')) {
+      Some(self.group()?)
+    } else {
+      None
+    };
+    let mut arguments = vec![self.argument()?.text];
+    arguments.extend_from_slice(parameters);
+    let call = Infix::atom(format!("{}({})", name, arguments.join(", ")));
+    Some(match power {
+      Some(power) => Infix {
+        text: format!("{} ^ {}", call.text, power.operand(6)),
+        prec: 5,
+      },
+      None => call,
+    })
+  }
+
+  // An operand with its exponents
+  fn atom(&mut self) -> Option<Infix> {
+    // e ^ x is the exponential function
+    if self.peek() == Some(&Token::Letter('e'))
+      && self.tokens.get(self.index + 1) == Some(&Token::Symbol('^'))
+    {
+      self.index += 2;
+      return Some(Infix::atom(format!("exp({})", self.group()?.text)));
+    }
+
+    let mut infix = self.primary()?;
+    while self.eat(Token::Symbol('^')) {
+      let power = self.group()?;
+      infix = Infix {
+        text: format!("{} ^ {}", infix.operand(6), power.operand(6)),
+        prec: 5,
+      };
+    }
+    Some(infix)
+  }
+
+  fn primary(&mut self) -> Option<Infix> {
+    let token = self.next()?;
+    let command = match token {
+      Token::Number(n) => return Some(Infix::atom(n)),
+      Token::Letter(c) => return self.variable(c.to_string()),
+      Token::Symbol('(') => return self.parentheses(Token::Symbol(')')),
+      Token::Symbol('[') => return self.parentheses(Token::Symbol(']')),
+      Token::Symbol('{') => return self.parentheses(Token::Symbol('}')),
+      Token::Symbol('|') => return self.call("abs", Token::Symbol('|')),
+      Token::Symbol(_) => return None,
+      Token::Command(c) => c,
+    };
+
+    match command.as_str() {
+      name if GREEK.contains(&name) => self.variable(command),
+      "left" => match self.next()? {
+        Token::Symbol('(') => self.parentheses(Token::Symbol(')')),
+        Token::Symbol('[') => self.parentheses(Token::Symbol(']')),
+        Token::Command(c) if c == "{" => self.parentheses(Token::Command(c)),
+        Token::Symbol('|') => self.call("abs", Token::Symbol('|')),
+        Token::Command(c) if c == "lfloor" => self.call("floor", Token::Command("rfloor".into())),
+        Token::Command(c) if c == "lceil" => self.call("ceil", Token::Command("rceil".into())),
+        _ => None,
+      },
+      "lfloor" => self.call("floor", Token::Command("rfloor".into())),
+      "lceil" => self.call("ceil", Token::Command("rceil".into())),
+      "frac" | "dfrac" | "tfrac" => {
+        let (numerator, denominator) = (self.group()?, self.group()?);
+        Some(Infix {
+          text: format!("{} / {}", numerator.operand(3), denominator.operand(4)),
+          prec: 3,
+        })
+      }
+      "sqrt" => {
+        if self.eat(Token::Symbol('[')) {
+          let n = self.expression(false)?;
+          self.expect(Token::Symbol(']'))?;
+          let x = self.group()?;
+          Some(Infix::atom(format!("root({}, {})", x.text, n.text)))
+        } else {
+          Some(Infix::atom(format!("sqrt({})", self.group()?.text)))
+        }
+      }
+      "sin" | "cos" | "tan" | "sec" | "csc" | "cot" | "ln" | "exp" => self.function(&command, &[]),
+      "log" => {
+        // Without a base the logarithm is the common logarithm
+        let base = if self.eat(Token::Symbol('_')) {
+          self.group()?.text
+        } else {
+          String::from("10")
+        };
+        self.function("log", &[base])
+      }
+      "operatorname" | "mathrm" => {
+        let name = self.raw()?;
+        if Operator::from(name.as_str()).compare(&Error) || !self.parenthesized() {
+          self.variable(name)
+        } else {
+          self.function(&name, &[])
+        }
+      }
+      "sum" | "prod" => {
+        // \sum_{k=a}^{b} expression
+        self.expect(Token::Symbol('_'))?;
+        self.expect(Token::Symbol('{'))?;
+        let k = self.expression(false)?;
+        self.expect(Token::Symbol('='))?;
+        let from = self.expression(false)?;
+        self.expect(Token::Symbol('}'))?;
+        self.expect(Token::Symbol('^'))?;
+        let to = self.group()?;
+        let body = self.expression(true)?;
+        Some(Infix::atom(format!(
+          "{}({}, {}, {}, {})",
+          command, body.text, k.text, from.text, to.text
+        )))
+      }
+      _ => None,
+    }
+  }
+
+  fn parentheses(&mut self, delimiter: Token) -> Option<Infix> {
+    let inner = self.expression(false)?;
+    self.close(delimiter)?;
+    Some(Infix::atom(inner.operand(6)))
+  }
+
+  fn call(&mut self, name: &str, delimiter: Token) -> Option<Infix> {
+    let inner = self.expression(false)?;
+    self.close(delimiter)?;
+    Some(Infix::atom(format!("{}({})", name, inner.text)))
+  }
+}
+
+// Joins operands and operators, adding parentheses where the precedence would change the meaning
+fn join(items: &[Item]) -> Option<Infix> {
+  let mut text = String::new();
+  let mut prec = 6;
+  for (i, item) in items.iter().enumerate() {
+    match item {
+      // A sign at the start
+      Item::Operator(operator) if i == 0 => text.push(*operator),
+      Item::Operator(operator) => {
+        prec = prec.min(precedence_of(*operator));
+        text.push_str(&format!(" {} ", operator));
+      }
+      Item::Infix(infix) => {
+        let before = match i.checked_sub(1).map(|i| &items[i]) {
+          Some(Item::Operator(operator)) => precedence_of(*operator) + 1,
+          _ => 0,
+        };
+        let after = match items.get(i + 1) {
+          Some(Item::Operator(operator)) => precedence_of(*operator),
+          _ => 0,
+        };
+        text.push_str(&infix.operand(before.max(after)));
+        if items.len() == 1 {
+          prec = infix.prec;
+        }
+      }
+    }
+  }
+  match items.last() {
+    Some(Item::Infix(_)) => Some(Infix { text, prec }),
+    _ => None,
+  }
+}
+
+/// Converts LaTeX to the infix notation of the parser
+/// Returns None if the LaTeX contains unsupported commands or unbalanced groups
+pub fn from_latex(latex: &str) -> Option<String> {
+  let mut reader = Reader {
+    tokens: tokenize(latex),
+    index: 0,
+  };
+  let mut text = reader.expression(false)?.text;
+  // An equation with an equals sign, like x^2 + y^2 = 25
+  if reader.eat(Token::Symbol('=')) {
+    text = format!("{} = {}", text, reader.expression(false)?.text);
+  }
+  match reader.peek() {
+    None => Some(text),
+    _ => None,
+  }
+}
@@ -27,20 +27,65 @@
 //!
 //! Look at [Operators](enum.Operator.html) for all available operators.
 
+mod codegen;
 mod component;
 mod derive;
+mod dot;
+mod dual;
 mod equation;
+mod evaluate;
+mod extrema;
+mod integrate;
+mod interval;
+mod latex;
+mod limit;
+mod mathml;
+mod ode;
 mod operators;
+mod optimize;
 mod parser;
+mod polynomial;
+mod pretty;
+mod quadrature;
+mod rational;
+#[cfg(feature = "serde")]
+mod serialize;
+mod sexpr;
+mod summation;
+mod tape;
+mod taylor;
 mod utils;
 
+pub use codegen::{rust_functions, Language};
 pub use component::Component;
 pub use equation::Equation;
+pub use extrema::{Classification, CriticalPoint, Extrema};
+pub use limit::Direction;
+pub use mathml::MathML;
+pub use ode::{solve_ode, Solution, Solver};
 pub use operators::Operator;
+pub use optimize::{minimize, minimize_within, Method, Minimum};
+pub use polynomial::{Coefficient, Polynomial, Rational};
+pub use pretty::{Charset, Pretty};
+pub use quadrature::{Integral, Quadrature};
+#[cfg(feature = "serde")]
+pub use serialize::infix;
+pub use tape::Tape;
 
 #[cfg(test)]
 mod tests {
+  use super::codegen::Language;
+  use super::component::Component;
   use super::equation::Equation;
+  use super::extrema::Classification;
+  use super::limit::Direction;
+  use super::mathml::MathML;
+  use super::ode::{Solution, Solver};
+  use super::operators::Operator;
+  use super::parser::parse;
+  use super::polynomial::{Coefficient, Polynomial, Rational};
+  use super::pretty::Charset;
+  use super::quadrature::Quadrature;
 
   #[test]
   fn it_works() {
@@ -67,4 +112,1075 @@ mod tests {
     println!("{:?}", Equation::new("x ^ 3").derive().text);
     println!("{:?}", Equation::new("x^sin(x)").derive().text);
   }
+
+  #[test]
+  fn parentheses_and_simplification() {
+    // A parenthesized term followed by an operator
+    assert_eq!(
+      Equation::new("(x - 1) ^ 2 + (x + 1) ^ 2")
+        .solve_with(vec![("x", 3.0)])
+        .to_float()
+        .unwrap(),
+      20.0
+    );
+    // A simplified term keeps the rest of the expression
+    assert_eq!(Equation::new("x * 1 + 2").text, "x * 1 + 2");
+    assert_eq!(Equation::new("x * 1 + 2").expression.to_string(), "x + 2");
+  }
+
+  #[test]
+  fn derive_rules() {
+    assert_eq!(Equation::new("4 ^ x").derive().text, "ln(4) * 4 ^ x");
+    assert_eq!(Equation::new("ln(x ^ 2)").derive().text, "2 * x / x ^ 2");
+    assert_eq!(Equation::new("exp(x)").derive().text, "exp(x)");
+
+    // % truncates, so the derivative matches automatic differentiation for negative quotients
+    let modulo = Equation::new("a % b");
+    let vars = vec![("a", -5.0), ("b", 3.0)];
+    assert_eq!(
+      modulo.derive_n("b", 1).solve_with(vars.clone()).to_float(),
+      Some(1.0)
+    );
+    assert_eq!(
+      modulo.eval_with_gradient(vars),
+      Some((-2.0, vec![1.0, 1.0]))
+    );
+  }
+
+  #[test]
+  fn higher_order_derivatives() {
+    let eq = Equation::new("x ^ 3 * y ^ 2 + sin(x)");
+    assert_eq!(eq.derive_n("x", 0).text, eq.text);
+    assert_eq!(
+      eq.derive_n("x", 4)
+        .solve_with(vec![("x", 1.0), ("y", 2.0)])
+        .to_float(),
+      Some(1.0_f32.sin())
+    );
+    assert_eq!(eq.derive_partials(&["x", "y", "y"]).text, "6 * x ^ 2");
+    assert_eq!(eq.derive_partials(&[]).text, eq.text);
+
+    // (x ^ a) ^ b is only merged for integer powers
+    let abs = Equation::new("(x ^ 2) ^ 0.5");
+    assert_eq!(
+      abs
+        .derive_n("x", 1)
+        .solve_with(vec![("x", -2.0)])
+        .to_float(),
+      Some(-1.0)
+    );
+    assert_eq!(
+      abs
+        .derive_n("x", 2)
+        .solve_with(vec![("x", -2.0)])
+        .to_float(),
+      Some(0.0)
+    );
+    assert!(Equation::new("(x ^ 0.5) ^ 2")
+      .derive_n("x", 2)
+      .solve_with(vec![("x", -2.0)])
+      .to_float()
+      .unwrap()
+      .is_nan());
+    assert_eq!(
+      Equation::new("(x ^ 2) ^ 3").derive_n("x", 2).text,
+      "30 * x ^ 4"
+    );
+
+    // Like terms are collected between derivatives, so repeated derivatives stay small and exact
+    let eq = Equation::new("1 / (1 - x)");
+    assert_eq!(eq.derive_n("x", 3).text, "6 / (1 - x) ^ 4");
+    let tenth = eq.derive_n("x", 10);
+    assert!(tenth.text.len() < 30);
+    assert_eq!(
+      tenth.solve_with(vec![("x", 0.0)]).to_float(),
+      Some(3628800.0)
+    );
+    assert_eq!(
+      Equation::new("sin(x) * cos(x)").derive_n("x", 2).text,
+      "-4 * (sin(x) * cos(x))"
+    );
+    assert!(Equation::new("1 / (1 + x ^ 2)").derive_n("x", 6).text.len() < 200);
+  }
+
+  #[test]
+  fn sums_and_products() {
+    let solve =
+      |text: &str, vars: Vec<(&str, f32)>| Equation::new(text).solve_with(vars).to_float().unwrap();
+
+    assert_eq!(solve("sum(k ^ 2, k, 1, 10)", vec![]), 385.0);
+    assert_eq!(solve("prod(k, k, 1, 5)", vec![]), 120.0);
+    assert_eq!(solve("sum(k, k, 1, 0) + prod(k, k, 1, 0)", vec![]), 1.0);
+    // The bound variable shadows k
+    assert_eq!(solve("sum(k, k, 1, 4) + k", vec![("k", 10.0)]), 20.0);
+    assert_eq!(
+      Equation::new("sum(x * k, k, 1, 4)")
+        .solve_for(20.0, vec![])
+        .1,
+      2.0
+    );
+
+    // Closed forms
+    assert_eq!(
+      Equation::new("sum(k, k, 1, n)").expression.to_string(),
+      "0.5 * n ^ 2 + 0.5 * n"
+    );
+    assert_eq!(
+      Equation::new("sum(2 ^ k, k, 0, n)").expression.to_string(),
+      "2 ^ (n + 1) - 1"
+    );
+    assert_eq!(
+      Equation::new("sum(x, k, 1, n)").expression.to_string(),
+      "n * x"
+    );
+    assert_eq!(solve("sum(k ^ 3, k, 1, n)", vec![("n", 100.0)]), 25502500.0);
+    // Bounds past 2 ^ 24 where not every integer is representable, and powers too high for Faulhaber's formula
+    assert_eq!(solve("sum(k, k, 16777216, 16777220)", vec![]), 83886090.0);
+    assert!(Equation::new("sum(k ^ 40, k, 1, n)")
+      .expression
+      .to_string()
+      .starts_with("sum"));
+    assert_eq!(
+      solve("sum(k ^ 40, k, 1, n)", vec![("n", 2.0)]),
+      1099511627777.0
+    );
+
+    let eq = Equation::new("prod(x + k, k, 1, 3)");
+    assert_eq!(
+      eq.derive().solve_with(vec![("x", 2.0)]).to_float().unwrap(),
+      47.0
+    );
+    assert_eq!(
+      eq.eval_with_gradient(vec![("x", 2.0)]),
+      Some((60.0, vec![47.0]))
+    );
+    assert_eq!(
+      Equation::new("sum(k * x ^ k, k, 1, n)")
+        .expression
+        .variables(),
+      ["n", "x"]
+    );
+  }
+
+  #[test]
+  fn derivative_matrices() {
+    let texts = |row: &[Equation]| row.iter().map(|eq| eq.text.clone()).collect::<Vec<_>>();
+
+    // No variables, variables which do not occur and constant equations
+    assert!(Equation::new("x ^ 2").gradient(&[]).is_empty());
+    assert_eq!(texts(&Equation::new("x ^ 2").gradient(&["y"])), ["0"]);
+    assert_eq!(texts(&Equation::new("5").gradient(&["x", "y"])), ["0", "0"]);
+    assert!(Equation::jacobian(&[], &["x"]).is_empty());
+    let jacobian = Equation::jacobian(&[Equation::new("x")], &[]);
+    assert!(jacobian.len() == 1 && jacobian[0].is_empty());
+
+    // The bound variable of a sum is not a variable of the equation
+    assert_eq!(
+      texts(&Equation::new("sum(k * x, k, 1, 3)").gradient(&["x", "k"])),
+      ["6", "0"]
+    );
+
+    // Rows and columns of unused variables are zero, and mixed partials are symmetric
+    let hessian = Equation::new("exp(x * y)").hessian(&["x", "y", "z"]);
+    assert_eq!(hessian[0][1].text, hessian[1][0].text);
+    assert_eq!(texts(&hessian[2]), ["0", "0", "0"]);
+    assert_eq!(
+      Equation::evaluate_matrix(&hessian, vec![("x", 1.0), ("y", 0.0), ("z", 0.0)]),
+      Some(vec![
+        vec![0.0, 1.0, 0.0],
+        vec![1.0, 1.0, 0.0],
+        vec![0.0, 0.0, 0.0]
+      ])
+    );
+    assert_eq!(Equation::evaluate_matrix(&hessian, vec![("x", 1.0)]), None);
+
+    // Repeated variables give repeated rows
+    let hessian = Equation::new("x ^ 2 * y").hessian(&["x", "x"]);
+    assert_eq!(texts(&hessian[0]), ["2 * y", "2 * y"]);
+    assert_eq!(texts(&hessian[1]), ["2 * y", "2 * y"]);
+
+    // Entries are not finite at poles
+    let hessian = Equation::new("1 / x").hessian(&["x"]);
+    assert_eq!(hessian[0][0].text, "2 / x ^ 3");
+    assert!(!Equation::evaluate_matrix(&hessian, vec![("x", 0.0)]).unwrap()[0][0].is_finite());
+  }
+
+  #[test]
+  fn implicit_differentiation() {
+    let slope = |text: &str, x: f32, y: f32| {
+      Equation::new(text)
+        .implicit_derivative("y", "x")
+        .map(|eq| eq.solve_with(vec![("x", x), ("y", y)]).to_float().unwrap())
+    };
+
+    assert_eq!(slope("x ^ 2 / 4 + y ^ 2 - 1", 1.0, 0.5), Some(-0.5));
+    // The folium of Descartes, which solve_for can not isolate
+    assert_eq!(slope("x ^ 3 + y ^ 3 - 6 * x * y", 3.0, 3.0), Some(-1.0));
+    // F does not depend on y
+    assert_eq!(slope("x ^ 2 - 1", 1.0, 0.0), None);
+
+    // Two sided equations
+    let circle =
+      Equation::new("x ^ 2 + y ^ 2").implicit_derivative_with(&Equation::new("25"), "y", "x");
+    assert_eq!(
+      circle
+        .unwrap()
+        .solve_with(vec![("x", 3.0), ("y", 4.0)])
+        .to_float(),
+      Some(-0.75)
+    );
+    assert!(Equation::new("y")
+      .implicit_derivative_with(&Equation::new("y + x"), "y", "x")
+      .is_none());
+  }
+
+  #[test]
+  fn degenerate_extrema() {
+    let extrema = |text: &str, a: f32, b: f32| Equation::new(text).critical_points("x", (a, b));
+    let inf = f32::INFINITY;
+
+    // Constant functions and single points
+    let constant = extrema("5", -1.0, 1.0).unwrap();
+    assert!(constant.critical_points.is_empty() && constant.inflection_points.is_empty());
+    assert_eq!((constant.minimum.1, constant.maximum.1), (5.0, 5.0));
+    let point = extrema("x ^ 2", 2.0, 2.0).unwrap();
+    assert_eq!((point.minimum, point.maximum), ((2.0, 4.0), (2.0, 4.0)));
+
+    // Reversed or infinite intervals, other variables and functions without values on the interval
+    assert_eq!(extrema("x ^ 2", 1.0, -1.0), None);
+    assert_eq!(extrema("x ^ 2", 0.0, inf), None);
+    assert_eq!(extrema("x * y", 0.0, 1.0), None);
+    assert_eq!(extrema("ln(x)", -2.0, -1.0), None);
+
+    // Flat critical points, where the second derivative is zero
+    let flat = extrema("x ^ 4", -1.0, 1.0).unwrap();
+    assert_eq!(
+      flat.critical_points[0].classification,
+      Classification::Minimum
+    );
+    assert!(flat.inflection_points.is_empty());
+    let saddle = extrema("x ^ 3", -1.0, 1.0).unwrap();
+    assert_eq!(
+      saddle.critical_points[0].classification,
+      Classification::Saddle
+    );
+    assert_eq!(saddle.inflection_points, vec![0.0]);
+
+    // Points without derivative, edges of the domain and poles
+    assert_eq!(extrema("abs(x)", -1.0, 2.0).unwrap().minimum, (0.0, 0.0));
+    assert_eq!(extrema("sqrt(x)", -1.0, 1.0).unwrap().minimum, (0.0, 0.0));
+    let pole = extrema("1 / x", -1.0, 1.0).unwrap();
+    assert_eq!((pole.minimum, pole.maximum), ((0.0, -inf), (0.0, inf)));
+    let pole = extrema("1 / x ^ 2", -1.0, 1.0).unwrap();
+    assert_eq!((pole.minimum, pole.maximum), ((-1.0, 1.0), (0.0, inf)));
+    // The float closest to pi / 2 has a finite tangent, the function is still unbounded on both sides
+    let tan = extrema("tan(x)", 0.0, 3.0).unwrap();
+    assert_eq!((tan.minimum.1, tan.maximum.1), (-inf, inf));
+    assert!((tan.maximum.0 - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    // The supremum is approached at the jumps but never reached
+    let modulo = extrema("x % 1", 0.0, 2.0).unwrap();
+    assert_eq!((modulo.minimum, modulo.maximum.1), ((0.0, 0.0), 1.0));
+    assert_eq!(extrema("floor(x)", 0.0, 2.5).unwrap().maximum, (2.0, 2.0));
+    // Endpoints which overflow
+    let wide = extrema("x ^ 2", f32::MIN, f32::MAX).unwrap();
+    assert_eq!((wide.minimum, wide.maximum.1), ((0.0, 0.0), inf));
+  }
+
+  #[test]
+  fn integration_checks() {
+    let integrate = |text: &str| Equation::new(text).integrate_wrt("x").map(|eq| eq.text);
+
+    assert_eq!(integrate("1 / x"), Some(String::from("ln(abs(x))")));
+    assert_eq!(
+      integrate("tan(x)"),
+      Some(String::from("-1 * ln(abs(cos(x)))"))
+    );
+    // abs(x) ^ 2 / 2 only has the right derivative for positive x
+    assert_eq!(integrate("abs(x)"), None);
+    // Logarithms of constants are kept exact
+    assert_eq!(integrate("2 ^ x"), Some(String::from("2 ^ x / ln(2)")));
+
+    // Substitution
+    assert_eq!(
+      integrate("2 * x * cos(x ^ 2)"),
+      Some(String::from("sin(x ^ 2)"))
+    );
+    assert_eq!(
+      integrate("sin(x) * cos(x)"),
+      Some(String::from("sin(x) ^ 2 / 2"))
+    );
+    // Expanded polynomials and partial fractions
+    assert_eq!(
+      integrate("x * (x + 1)"),
+      Some(String::from("x ^ 3 / 3 + x ^ 2 / 2"))
+    );
+    assert_eq!(
+      integrate("1 / (x ^ 2 - 1)"),
+      Some(String::from("ln(abs(x - 1)) / 2 - ln(abs(x + 1)) / 2"))
+    );
+    // Integration by parts, repeated for higher powers
+    assert_eq!(
+      integrate("x * exp(x)"),
+      Some(String::from("x * exp(x) - exp(x)"))
+    );
+    assert_eq!(
+      integrate("x ^ 2 * exp(x)"),
+      Some(String::from("x ^ 2 * exp(x) - 2 * (x * exp(x) - exp(x))"))
+    );
+
+    // Step functions have no continuous antiderivative
+    for text in &["x % 2", "floor(x)", "x * ceil(x)", "round(x ^ 2)"] {
+      assert_eq!(integrate(text), None, "{}", text);
+    }
+    assert_eq!(integrate("floor(2) * x"), Some(String::from("x ^ 2")));
+  }
+
+  #[test]
+  fn numeric_integration() {
+    let integrate = |text: &str, a: f32, b: f32, method: Quadrature| {
+      Equation::new(text).integrate_numeric_with("x", a, b, method)
+    };
+
+    for method in [Quadrature::GaussKronrod, Quadrature::Simpson] {
+      let integral = integrate("x ^ 2", 0.0, 3.0, method).unwrap();
+      assert!((integral.value - 9.0).abs() < 1e-5 && integral.error < 1e-4);
+      // Reversed bounds negate the value
+      assert_eq!(integrate("x", 2.0, 0.0, method).unwrap().value, -2.0);
+      assert_eq!(integrate("sin(x)", 1.0, 1.0, method).unwrap().value, 0.0);
+
+      let integral = integrate("1 / x ^ 2", 1.0, f32::INFINITY, method).unwrap();
+      assert!((integral.value - 1.0).abs() < 1e-5);
+      let integral = integrate("exp(x)", f32::NEG_INFINITY, 0.0, method).unwrap();
+      assert!((integral.value - 1.0).abs() < 1e-5);
+
+      // Singular and undefined integrands and unknown variables fail
+      assert_eq!(integrate("1 / x", 0.0, 1.0, method), None);
+      assert_eq!(integrate("sqrt(x)", -1.0, 1.0, method), None);
+      assert_eq!(integrate("x * y", 0.0, 1.0, method), None);
+    }
+
+    // Gauss-Kronrod does not evaluate the endpoints, so it handles integrable singularities there
+    let integral = integrate("1 / sqrt(x)", 0.0, 1.0, Quadrature::GaussKronrod).unwrap();
+    assert!((integral.value - 2.0).abs() < 1e-4);
+    assert!((integral.value - 2.0).abs() <= integral.error);
+  }
+
+  #[test]
+  fn limits() {
+    let limit = |text: &str, point: f32, direction: Direction| {
+      Equation::new(text).limit("x", point, direction)
+    };
+    let inf = f32::INFINITY;
+
+    // One-sided limits, which differ at jumps and poles
+    assert_eq!(limit("1 / x", 0.0, Direction::Right), Some(inf));
+    assert_eq!(limit("1 / x", 0.0, Direction::Left), Some(-inf));
+    assert_eq!(limit("1 / x", 0.0, Direction::Both), None);
+    assert_eq!(limit("1 / x ^ 2", 0.0, Direction::Both), Some(inf));
+    assert_eq!(limit("floor(x)", 1.0, Direction::Left), Some(0.0));
+    assert_eq!(limit("floor(x)", 1.0, Direction::Right), Some(1.0));
+    assert_eq!(limit("floor(x)", 1.0, Direction::Both), None);
+    assert_eq!(limit("abs(x) / x", 0.0, Direction::Right), Some(1.0));
+    assert_eq!(limit("abs(x) / x", 0.0, Direction::Left), Some(-1.0));
+    assert_eq!(limit("exp(1 / x)", 0.0, Direction::Left), Some(0.0));
+    assert_eq!(limit("exp(1 / x)", 0.0, Direction::Right), Some(inf));
+
+    // Steps and small constants relative to the scale of the expression
+    assert_eq!(limit("floor(2000 * x)", 0.0, Direction::Right), Some(0.0));
+    assert_eq!(limit("floor(2000 * x)", 0.0, Direction::Left), Some(-1.0));
+    assert_eq!(limit("round(x)", 0.4999, Direction::Right), Some(0.0));
+    assert_eq!(limit("round(x)", 0.5, Direction::Left), Some(0.0));
+    assert_eq!(limit("round(x)", 0.5, Direction::Right), Some(1.0));
+    assert_eq!(limit("ceil(x ^ 2)", 0.0, Direction::Both), Some(1.0));
+    assert_eq!(limit("x % 1", 1.0, Direction::Left), Some(1.0));
+    assert_eq!(limit("x % 1", 1.0, Direction::Right), Some(0.0));
+    assert_eq!(limit("floor(sin(x) / x)", 0.0, Direction::Both), Some(0.0));
+    assert_eq!(limit("0.0000001 / x", 0.0, Direction::Right), Some(inf));
+    assert_eq!(
+      limit("x / (x + 0.0000001)", 0.0, Direction::Right),
+      Some(0.0)
+    );
+    assert_eq!(
+      limit(
+        "sin(x) / (x - 3.1415927)",
+        std::f32::consts::PI,
+        Direction::Both
+      ),
+      Some(-1.0)
+    );
+
+    // Functions only defined on one side of the point
+    assert_eq!(limit("sqrt(x)", 0.0, Direction::Right), Some(0.0));
+    assert_eq!(limit("sqrt(x)", 0.0, Direction::Left), None);
+    assert_eq!(limit("ln(x)", 0.0, Direction::Right), Some(-inf));
+    assert_eq!(limit("x * ln(x)", 0.0, Direction::Right), Some(0.0));
+
+    // Divergent limits without an infinite value
+    assert_eq!(limit("sin(1 / x)", 0.0, Direction::Both), None);
+    assert_eq!(limit("sin(x)", inf, Direction::Both), None);
+    assert_eq!(limit("x * y", 0.0, Direction::Both), None);
+  }
+
+  #[test]
+  fn taylor_remainder() {
+    // The maximum of the derivative lies at 1 - 1 / sqrt(2), the bound is guaranteed and close to it
+    let eq = Equation::new("exp(0 - (x - 1) ^ 2)");
+    let bound = eq.remainder_bound("x", 0.0, 0, 2.0).unwrap();
+    let maximum = 2.0 * 2f32.sqrt() * (-0.5f32).exp();
+    assert!(bound >= maximum && bound < maximum * 1.002);
+
+    // Exact coefficients for rational functions, and the remainder of a polynomial beyond its degree
+    let series = Equation::new("1 / (1 - x)").taylor("x", 0.0, 10).unwrap();
+    assert_eq!(series.solve_with(vec![("x", 2.0)]).to_float(), Some(2047.0));
+    assert_eq!(
+      Equation::new("x / (x ^ 2 + 1)")
+        .taylor("x", 0.0, 5)
+        .unwrap()
+        .text,
+      "x - x ^ 3 + x ^ 5"
+    );
+    assert_eq!(
+      Equation::new("x ^ 3").remainder_bound("x", 0.0, 3, 10.0),
+      Some(0.0)
+    );
+
+    // The derivative is not finite between about and x
+    assert_eq!(
+      Equation::new("ln(x)").remainder_bound("x", 1.0, 2, -1.0),
+      None
+    );
+    assert_eq!(
+      Equation::new("1 / (1 - x)").remainder_bound("x", 0.0, 2, 2.0),
+      None
+    );
+    assert_eq!(
+      Equation::new("x * y").remainder_bound("x", 1.0, 2, 2.0),
+      None
+    );
+  }
+
+  #[test]
+  fn ode_steps() {
+    let adaptive = Solver::DormandPrince { tolerance: 1e-5 };
+    let last = |solution: &Solution| solution.states.last().unwrap()[0];
+
+    // A stiff equation, which follows cos(t) + sin(t) / 1000 after a fast transient
+    let stiff = Equation::new("0 - 1000 * (y - cos(t))");
+    let expected = 1f32.cos() + 1f32.sin() / 1000.0;
+    let solution = stiff.solve_ode("y", "t", 0.0, 0.0, 1.0, adaptive).unwrap();
+    assert!((last(&solution) - expected).abs() < 1e-4);
+    // Fixed steps which are too large for the fast transient are unstable
+    assert_eq!(
+      stiff.solve_ode("y", "t", 0.0, 0.0, 1.0, Solver::Rk4 { step: 0.01 }),
+      None
+    );
+    let solution = stiff
+      .solve_ode("y", "t", 0.0, 0.0, 1.0, Solver::Rk4 { step: 0.001 })
+      .unwrap();
+    assert!((last(&solution) - expected).abs() < 1e-4);
+
+    // Steps are rejected and shrunk around a narrow pulse, and grow again afterwards
+    let pulse = Equation::new("1000 * exp(0 - 10000 * (t - 0.5) ^ 2)");
+    let solution = pulse.solve_ode("y", "t", 0.0, 0.0, 1.0, adaptive).unwrap();
+    assert!((last(&solution) - 1000.0 * (std::f32::consts::PI / 10000.0).sqrt()).abs() < 1e-3);
+    let steps: Vec<_> = solution.times.windows(2).map(|w| w[1] - w[0]).collect();
+    assert!(steps.iter().any(|h| *h < 0.01) && steps.iter().any(|h| *h > 0.1));
+
+    // y' = y ^ 2 with y(0) = 1 blows up at t = 1, the step size shrinks until the solver gives up
+    let blow_up = Equation::new("y ^ 2");
+    assert_eq!(blow_up.solve_ode("y", "t", 0.0, 1.0, 2.0, adaptive), None);
+    let solution = blow_up
+      .solve_ode("y", "t", 0.0, 1.0, 0.9, adaptive)
+      .unwrap();
+    assert!((last(&solution) - 10.0).abs() < 1e-2);
+
+    // Backwards in time, without steps and with an invalid step size
+    let growth = Equation::new("y");
+    let solution = growth.solve_ode("y", "t", 1.0, 1.0, 0.0, adaptive).unwrap();
+    assert!((last(&solution) - (-1f32).exp()).abs() < 1e-5);
+    assert_eq!(
+      growth
+        .solve_ode("y", "t", 1.0, 1.0, 1.0, adaptive)
+        .unwrap()
+        .times,
+      vec![1.0]
+    );
+    assert_eq!(
+      growth.solve_ode("y", "t", 0.0, 1.0, 1.0, Solver::Rk4 { step: 0.0 }),
+      None
+    );
+  }
+
+  #[test]
+  fn polynomial_coefficients() {
+    let polynomial =
+      |text: &str| -> Polynomial { Polynomial::from_component(&parse(text)).unwrap() };
+
+    // Small coefficients are not dropped
+    let p = polynomial("0.00001 * x ^ 2 - x");
+    assert_eq!(p.to_string(), "0.00001 * x ^ 2 - x");
+    assert_eq!(p.roots(), Some(vec![0.0, 100000.0]));
+    // Roots far from 1 are found even when the ratio of the coefficients overflows
+    assert_eq!(
+      polynomial("0.0000000000000000000001 * x ^ 2 - 100000000000000000000").roots(),
+      Some(vec![-1e21, 1e21])
+    );
+    // Powers are not expanded without limit
+    assert!(Polynomial::<f32>::from_component(&parse("x ^ 4000000000")).is_none());
+
+    // The gcd of float polynomials ignores the rounding noise of the remainders
+    assert_eq!(
+      polynomial("x ^ 3 - 2.25 * x")
+        .gcd(&polynomial("x ^ 2 - 1.5 * x"))
+        .to_string(),
+      "x ^ 2 - 1.5 * x"
+    );
+
+    // Numbers which don't fit in a rational
+    assert!(Polynomial::<Rational>::from_component(&parse("100000000000000000000 * x")).is_none());
+    assert!(Polynomial::<Rational>::from_component(&parse("0.5 * x")).is_some());
+
+    let big = Rational::new(i64::MAX, 3);
+    assert_eq!(
+      big.checked_mul(Rational::new(3, 1)),
+      Some(Rational::new(i64::MAX, 1))
+    );
+    assert_eq!(big.checked_mul(Rational::new(2, 1)), None);
+    assert_eq!(big.checked_div(Rational::new(0, 1)), None);
+    // Overflow gives an invalid fraction, which stays invalid like NaN
+    let invalid = big * Rational::new(2, 1);
+    assert!(invalid.to_float().is_nan());
+    assert!((invalid * Rational::new(0, 1)).to_float().is_nan());
+    assert_eq!(
+      (Rational::new(1, 1) / Rational::new(0, 1)).to_string(),
+      "NaN"
+    );
+  }
+
+  #[test]
+  fn rational_functions() {
+    let together = |text: &str| Equation::new(text).together().text;
+    let cancel = |text: &str| Equation::new(text).cancel().text;
+    let apart = |text: &str| Equation::new(text).apart("x").text;
+
+    assert_eq!(together("1 / x + 1 / y"), "(y + x) / (x * y)");
+    assert_eq!(together("x / (x - 1) - 1 / (x - 1)"), "(x - 1) / (x - 1)");
+
+    assert_eq!(
+      cancel("(x ^ 3 - 1) / (x ^ 2 - 1)"),
+      "(x ^ 2 + x + 1) / (x + 1)"
+    );
+    assert_eq!(cancel("(x ^ 2 + 1) / (x - 1)"), "(x ^ 2 + 1) / (x - 1)");
+    // Only polynomial factors are cancelled
+    assert_eq!(cancel("sin(x) / sin(x)"), "sin(x) / sin(x)");
+
+    assert_eq!(
+      apart("x ^ 3 / (x ^ 2 - 1)"),
+      "x + 0.5 / (x - 1) + 0.5 / (x + 1)"
+    );
+    assert_eq!(apart("1 / (x ^ 2 + 1)"), "1 / (x ^ 2 + 1)");
+    // Nearby poles are kept apart, also when they are large
+    assert_eq!(
+      apart("1 / ((x - 1) * (x - 1.01))"),
+      "100 / (x - 1.01) - 100 / (x - 1)"
+    );
+    assert_eq!(
+      apart("1 / ((x - 100) * (x - 101))"),
+      "1 / (x - 101) - 1 / (x - 100)"
+    );
+    // Repeated poles are merged
+    let eq = Equation::new("1 / ((x - 1) ^ 3 * (x + 2))");
+    let decomposed = eq.apart("x");
+    assert!(decomposed.text.contains("/ (x - 1) ^ 3"));
+    for x in [-1.0, 0.0, 0.5, 3.0] {
+      let expected = eq.solve_with(vec![("x", x)]).to_float().unwrap();
+      let actual = decomposed.solve_with(vec![("x", x)]).to_float().unwrap();
+      assert!((expected - actual).abs() < 1e-4 * expected.abs());
+    }
+  }
+
+  #[test]
+  fn latex() {
+    let latex = |text: &str| Equation::new(text).to_latex();
+
+    assert_eq!(latex("a - (b - c)"), "a - \\left(b - c\\right)");
+    assert_eq!(latex("(a ^ b) ^ c"), "\\left(a^{b}\\right)^{c}");
+    assert_eq!(latex("(a / b) ^ 2"), "\\left(\\frac{a}{b}\\right)^{2}");
+    assert_eq!(latex("x * (y % 3)"), "x \\cdot \\left(y \\bmod 3\\right)");
+    assert_eq!(
+      latex("log(x, b) * exp(2 * x)"),
+      "\\log_{b}\\left(x\\right) \\cdot e^{2 \\cdot x}"
+    );
+    assert_eq!(
+      latex("abs(theta_i) + alpha"),
+      "\\left|\\theta_{i}\\right| + \\alpha"
+    );
+    assert_eq!(
+      latex("sum(1 / k, k, 1, n + 1)"),
+      "\\sum_{k=1}^{n + 1} \\frac{1}{k}"
+    );
+    assert_eq!(
+      latex("prod(x + k, k, 1, n)"),
+      "\\prod_{k=1}^{n} \\left(x + k\\right)"
+    );
+  }
+
+  #[test]
+  fn latex_input() {
+    let (x, y, n, speed) = (2.5f32, 2.5f32, 4.0f32, 7.0f32);
+    let vars = vec![
+      ("x", x),
+      ("y", y),
+      ("a", 1.0),
+      ("b", 2.0),
+      ("c", 3.0),
+      ("d", 4.0),
+      ("beta_10", -2.0),
+      ("n", n),
+      ("pi", std::f32::consts::PI),
+      ("speed", speed),
+    ];
+    for (latex, text, value) in &[
+      (
+        r"\frac{x^2}{2} + \sqrt{y}",
+        "x ^ 2 / 2 + sqrt(y)",
+        x * x / 2.0 + y.sqrt(),
+      ),
+      (
+        r"\sqrt[3]{x+1} \times \lfloor y \rfloor",
+        "root(x + 1, 3) * floor(y)",
+        (x + 1.0).cbrt() * y.floor(),
+      ),
+      (
+        r"\frac{a+b}{c d} - x^23",
+        "(a + b) / (c * d) - x ^ 2 * 3",
+        3.0 / 12.0 - x * x * 3.0,
+      ),
+      (
+        r"\sin^2 x + \cos\left(x\right)^{2}",
+        "sin(x) ^ 2 + cos(x) ^ 2",
+        1.0,
+      ),
+      (
+        r"\log_{2}(x) \cdot \left|\beta_{10}\right|",
+        "log(x, 2) * abs(beta_10)",
+        x.log2() * 2.0,
+      ),
+      (
+        r"e^{x} \bmod 2\pi",
+        "exp(x) % (2 * pi)",
+        x.exp() % (2.0 * std::f32::consts::PI),
+      ),
+      (
+        r"\sum_{k=1}^{n} \frac{1}{k} + 1",
+        "sum(1 / k, k, 1, n) + 1",
+        1.0 + 1.0 / 2.0 + 1.0 / 3.0 + 1.0 / 4.0 + 1.0,
+      ),
+      (
+        r"\operatorname{round}(x) + \mathrm{speed}",
+        "round(x) + speed",
+        x.round() + speed,
+      ),
+    ] {
+      let eq = Equation::from_latex(latex).unwrap();
+      assert_eq!(eq.text, *text);
+      let solved = eq.solve_with(vars.clone()).to_float().unwrap();
+      assert!((solved - value).abs() < 1e-5 * value.abs().max(1.0));
+    }
+
+    assert!(Equation::from_latex(r"\unknown{x}").is_none());
+    assert!(Equation::from_latex(r"\frac{x}{2").is_none());
+  }
+
+  #[test]
+  fn mathml() {
+    let presentation = |text: &str| {
+      let markup = Equation::new(text)
+        .expression
+        .to_mathml(MathML::Presentation);
+      markup[49..markup.len() - 7].to_string()
+    };
+    let content = |text: &str| {
+      let markup = Equation::new(text).expression.to_mathml(MathML::Content);
+      markup[49..markup.len() - 7].to_string()
+    };
+
+    assert_eq!(
+      presentation("a - (b - c)"),
+      "<mrow><mi>a</mi><mo>-</mo><mrow><mo>(</mo><mrow><mi>b</mi><mo>-</mo><mi>c</mi></mrow><mo>)</mo></mrow></mrow>"
+    );
+    assert_eq!(
+      presentation("root(x, 3) ^ alpha_1"),
+      "<msup><mroot><mi>x</mi><mn>3</mn></mroot><msub><mi>α</mi><mn>1</mn></msub></msup>"
+    );
+    assert_eq!(
+      presentation("log(x, 2)"),
+      "<mrow><msub><mi>log</mi><mn>2</mn></msub><mo>&#x2061;</mo><mrow><mo>(</mo><mi>x</mi><mo>)</mo></mrow></mrow>"
+    );
+    assert_eq!(
+      presentation("prod(x + k, k, 1, n)"),
+      "<mrow><munderover><mo>&#x220F;</mo><mrow><mi>k</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover><mrow><mo>(</mo><mrow><mi>x</mi><mo>+</mo><mi>k</mi></mrow><mo>)</mo></mrow></mrow>"
+    );
+
+    assert_eq!(
+      content("log(x, 2) % ceil(y)"),
+      "<apply><rem/><apply><log/><logbase><cn>2</cn></logbase><ci>x</ci></apply><apply><ceiling/><ci>y</ci></apply></apply>"
+    );
+    assert_eq!(
+      content("sum(1 / k, k, 1, n)"),
+      "<apply><sum/><bvar><ci>k</ci></bvar><lowlimit><cn>1</cn></lowlimit><uplimit><ci>n</ci></uplimit><apply><divide/><cn>1</cn><ci>k</ci></apply></apply>"
+    );
+    assert_eq!(
+      content("round(x) * sec(x)"),
+      "<apply><times/><apply><csymbol cd=\"rounding1\">round</csymbol><ci>x</ci></apply><apply><sec/><ci>x</ci></apply></apply>"
+    );
+  }
+
+  #[test]
+  fn pretty() {
+    let pretty =
+      |text: &str, charset: Charset| Equation::new(text).expression.pretty(charset).to_string();
+
+    assert_eq!(
+      pretty("(x + 1) / (x - 1) + y ^ 2", Charset::Unicode),
+      " x + 1     2\n─────── + y\n x - 1"
+    );
+    assert_eq!(
+      pretty("root(x / 2, 3) * abs(alpha_1)", Charset::Unicode),
+      "3    ____\n    ╱  x\n   ╱  ─── ⋅ │α₁│\n ╲╱    2"
+    );
+    assert_eq!(
+      pretty("(a / b) ^ 2 - log(x, 2)", Charset::Ascii),
+      "     2\n/ a \\\n|---|  - log (x)\n\\ b /       2"
+    );
+    assert_eq!(
+      pretty("sum(1 / k ^ 2, k, 1, n)", Charset::Ascii),
+      "  n\n ___\n \\     1\n /    ----\n ---    2\nk = 1  k"
+    );
+  }
+
+  #[test]
+  fn dot() {
+    let dot = Equation::new("log(x + y, 2) - x")
+      .expression
+      .to_dot(Some("y"));
+
+    assert_eq!(dot.matches(" -> ").count(), 6);
+    assert_eq!(dot.matches(", color=red").count(), 7);
+    assert!(dot.contains("n0 [label=\"-\", shape=ellipse, color=red"));
+    assert!(dot.contains("n6 [label=\"x\", shape=box];"));
+    assert!(dot.contains("n1 -> n5 [label=\"1\"];"));
+    assert!(!Equation::new("x").expression.to_dot(None).contains("red"));
+  }
+
+  #[test]
+  fn rust_code() {
+    let rust = |text: &str| Equation::new(text).to_rust_fn("f", &["x"]);
+
+    assert_eq!(
+      rust("(-2) ^ x - root(x, -3) % 2").unwrap(),
+      "fn f(x: f64) -> f64 {\n    (-2.0).powf(x) - x.powf(1.0 / -3.0) % 2.0\n}\n"
+    );
+    assert!(rust("sum(x / k, k, 1, n)").is_none());
+    assert!(rust("x + y").is_none());
+    assert!(rust("prod(x + k, k, 1, x)")
+      .unwrap()
+      .contains("std::iter::successors(Some(1.0), |k| Some(k + 1.0)).take_while(|&k| k <= x).map(|k| x + k).product::<f64>()"));
+  }
+
+  #[test]
+  fn shader_code() {
+    let code = |text: &str, language: Language| {
+      Equation::new(text)
+        .to_code(language, "f", &["x", "t0"])
+        .unwrap()
+    };
+
+    assert_eq!(
+      code("(x + t0) ^ 3 + x % t0 + round(x)", Language::Glsl),
+      "float f(float x, float t0) {
+    float t1 = x + t0;
+    return t1 * t1 * t1 + (x - t0 * trunc(x / t0)) + sign(x) * floor(abs(x) + 0.5);
+}
+"
+    );
+    assert_eq!(
+      code(
+        "cot(x) * root(x, t0) + sum(sqrt(x) * k + sqrt(x), k, 1, t0)",
+        Language::Wgsl
+      ),
+      "fn f(x: f32, t0: f32) -> f32 {
+    let t2 = sqrt(x);
+    var t1: f32 = 0.0;
+    for (var k: f32 = 1.0; k <= t0; k += 1.0) {
+        t1 += t2 * k + t2;
+    }
+    return 1.0 / tan(x) * pow(x, 1.0 / t0) + t1;
+}
+"
+    );
+    assert_eq!(
+      code("abs(x) % t0 + x ^ 2", Language::C),
+      "double f(double x, double t0) {
+    return fmod(fabs(x), t0) + pow(x, 2.0);
+}
+"
+    );
+    assert!(Equation::new("x + y")
+      .to_code(Language::C, "f", &["x"])
+      .is_none());
+  }
+
+  #[test]
+  fn sexpr() {
+    let text = |sexpr: &str| Component::from_sexpr(sexpr).map(|c| c.to_string());
+
+    assert_eq!(text("(+ a b c d)").unwrap(), "a + b + c + d");
+    assert_eq!(text("(/ a b c)").unwrap(), "a / b / c");
+    assert_eq!(text("(- a (- b c))").unwrap(), "a - (b - c)");
+    assert_eq!(text("(- (^ 2 2))").unwrap(), "-1 * 2 ^ 2");
+    assert_eq!(text("(* (- 3) (/ x))").unwrap(), "-3 * (1 / x)");
+    assert_eq!(text("(+)").unwrap(), "0");
+    assert_eq!(text(" ( log  x\n 2 ) ").unwrap(), "log(x, 2)");
+    assert_eq!(
+      text("(prod (+ x k) k 1 alpha_1)").unwrap(),
+      "prod(x + k, k, 1, alpha_1)"
+    );
+    for invalid in &[
+      "",
+      "(",
+      "(+ x",
+      "(+ x))",
+      "(foo x)",
+      "(-)",
+      "(pow x)",
+      "((+ x) y)",
+      "(+ x ?)",
+    ] {
+      assert!(Component::from_sexpr(invalid).is_none(), "{}", invalid);
+    }
+
+    let mut state = 0x9e37_79b9_7f4a_7c15;
+    for _ in 0..2000 {
+      let component = random_component(&mut state, 4);
+      let sexpr = component.to_sexpr();
+      assert_eq!(
+        Component::from_sexpr(&sexpr).unwrap(),
+        component,
+        "{}",
+        sexpr
+      );
+    }
+  }
+
+  #[test]
+  fn automatic_differentiation() {
+    let vars = ["x", "y"];
+    let point = [1.3, 2.1];
+    let close = |a: f32, b: f32| (a - b).abs() <= 1e-4 * a.abs().max(1.0);
+
+    for text in &[
+      "x + y - x * y / (x - y)",
+      "x % y + x ^ y + pow(y, x)",
+      "log(x, y) + root(y, x) * ln(x * y)",
+      "sin(x) * cos(y) + tan(x * y)",
+      "sec(x) + csc(y) - cot(x + y)",
+      "abs(x - y) + floor(x) * round(y) + ceil(x * y)",
+      "exp(x / y) * sqrt(x + y)",
+    ] {
+      let eq = Equation::new(*text);
+      let (value, gradient) = eq.tape(&vars).unwrap().gradient(&point).unwrap();
+      let (forward_value, forward_gradient) =
+        eq.eval_with_gradient(vec![("x", 1.3), ("y", 2.1)]).unwrap();
+      let solved = eq
+        .solve_with(vec![("x", 1.3), ("y", 2.1)])
+        .to_float()
+        .unwrap();
+
+      assert!(
+        close(value, solved) && close(forward_value, solved),
+        "{}",
+        text
+      );
+      for (i, derivative) in eq.gradient(&vars).iter().enumerate() {
+        let expected = derivative
+          .solve_with(vec![("x", 1.3), ("y", 2.1)])
+          .to_float()
+          .unwrap();
+        assert!(
+          close(gradient[i], expected),
+          "{} d{}: {} != {}",
+          text,
+          vars[i],
+          gradient[i],
+          expected
+        );
+        assert!(
+          close(forward_gradient[i], expected),
+          "{} d{}",
+          text,
+          vars[i]
+        );
+      }
+    }
+
+    // The amount of values has to match the variables of the tape
+    let tape = Equation::new("x * y").tape(&vars).unwrap();
+    assert_eq!(tape.gradient(&[1.0]), None);
+    assert_eq!(tape.gradient(&[1.0, 2.0, 3.0]), None);
+    assert_eq!(tape.gradient(&[3.0, 2.0]), Some((6.0, vec![2.0, 3.0])));
+  }
+
+  // Builds a random component from a xorshift generator, with every kind of operator
+  fn random_component(state: &mut u64, depth: u32) -> Component {
+    let mut next = |n: u64| {
+      *state ^= *state << 13;
+      *state ^= *state >> 7;
+      *state ^= *state << 17;
+      (*state % n) as usize
+    };
+
+    // Zero and one are left out, the parser removes them from products and powers
+    const NUMBERS: [f32; 10] = [2.0, 3.0, 0.5, 2.5, 10.0, -2.0, -0.5, -3.0, 0.001, 123.25];
+    const VARIABLES: [&str; 4] = ["x", "y", "alpha", "x1"];
+    const BINARY: [Operator; 9] = [
+      Operator::Add,
+      Operator::Subtract,
+      Operator::Multiply,
+      Operator::Divide,
+      Operator::Modulo,
+      Operator::Exponent,
+      Operator::Pow,
+      Operator::Log,
+      Operator::Root,
+    ];
+    const UNARY: [Operator; 13] = [
+      Operator::Sin,
+      Operator::Cos,
+      Operator::Tan,
+      Operator::Sec,
+      Operator::Csc,
+      Operator::Cot,
+      Operator::Abs,
+      Operator::Floor,
+      Operator::Round,
+      Operator::Ceil,
+      Operator::Exp,
+      Operator::Ln,
+      Operator::Sqrt,
+    ];
+
+    let kind = if depth == 0 { next(2) } else { next(8) };
+    let index = next(1 << 16);
+    match kind {
+      0 => Component::Number(NUMBERS[index % NUMBERS.len()]),
+      1 => Component::Variable(VARIABLES[index % VARIABLES.len()].to_string()),
+      2 => Component::Function {
+        operator: UNARY[index % UNARY.len()].clone(),
+        values: vec![random_component(state, depth - 1)],
+      },
+      3 => Component::Function {
+        operator: if index % 2 == 0 {
+          Operator::Sum
+        } else {
+          Operator::Product
+        },
+        values: vec![
+          random_component(state, depth - 1),
+          Component::Variable(String::from("k")),
+          Component::Number(2.0),
+          random_component(state, depth - 1),
+        ],
+      },
+      _ => Component::Function {
+        operator: BINARY[index % BINARY.len()].clone(),
+        values: vec![
+          random_component(state, depth - 1),
+          random_component(state, depth - 1),
+        ],
+      },
+    }
+  }
+
+  #[test]
+  fn display_round_trip() {
+    let text = |text: &str| parse(text).to_string();
+
+    assert_eq!(text("a - (b - c)"), "a - (b - c)");
+    assert_eq!(text("a / (b * c)"), "a / (b * c)");
+    assert_eq!(text("(a ^ b) ^ c"), "(a ^ b) ^ c");
+    assert_eq!(text("a ^ b ^ c"), "a ^ b ^ c");
+    assert_eq!(parse("a ^ b ^ c"), parse("a ^ (b ^ c)"));
+    assert_eq!(text("(-2) ^ 2 - -x"), "(-2) ^ 2 - -1 * x");
+    assert_eq!(Equation::new("-2 ^ 2").expression.to_float(), Some(-4.0));
+
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    for _ in 0..2000 {
+      let component = random_component(&mut state, 4);
+      let text = component.to_string();
+      assert_eq!(parse(&text), component, "{}", text);
+    }
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn serde() {
+    let eq = Equation::new("sum(k ^ 2, k, 1, n) + log(x, 2) - -y");
+    let json = serde_json::to_string(&eq).unwrap();
+    let back: Equation = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(back.text, eq.text);
+    assert_eq!(back.expression, eq.expression);
+    assert_eq!(
+      serde_json::to_string(&Component::Variable(String::from("x"))).unwrap(),
+      "\"x\""
+    );
+    assert_eq!(
+      serde_json::from_str::<Component>(r#"{"op":"pow","args":["x",3]}"#).unwrap(),
+      Component::Function {
+        operator: Operator::Pow,
+        values: vec![
+          Component::Variable(String::from("x")),
+          Component::Number(3.0)
+        ],
+      }
+    );
+    assert!(serde_json::from_str::<Component>(r#"{"op":"foo","args":[]}"#).is_err());
+    assert!(serde_json::from_str::<Component>(r#"{"args":[1]}"#).is_err());
+    assert!(serde_json::from_str::<Component>(r#"{"op":"+","args":["x"]}"#).is_err());
+    assert!(serde_json::from_str::<Component>(r#"{"op":"sin","args":["x","y"]}"#).is_err());
+    assert!(serde_json::from_str::<Component>(r#"{"op":"sum","args":["k",1,"n"]}"#).is_err());
+    assert!(serde_json::from_str::<Component>(r#"{"num":"x"}"#).is_err());
+
+    // Non finite numbers survive a round trip
+    let infinite = Component::Function {
+      operator: Operator::Add,
+      values: vec![
+        Component::Number(f32::INFINITY),
+        Component::Number(f32::NEG_INFINITY),
+      ],
+    };
+    let json = serde_json::to_string(&infinite).unwrap();
+    assert_eq!(
+      json,
+      r#"{"op":"+","args":[{"num":"inf"},{"num":"-inf"}]}"#
+    );
+    assert_eq!(serde_json::from_str::<Component>(&json).unwrap(), infinite);
+    let nan = serde_json::to_string(&Component::Number(f32::NAN)).unwrap();
+    assert_eq!(nan, r#"{"num":"NaN"}"#);
+    match serde_json::from_str::<Component>(&nan).unwrap() {
+      Component::Number(f) => assert!(f.is_nan()),
+      other => panic!("{:?}", other),
+    }
+
+    // Variables named like non finite numbers stay variables
+    let eq = Equation::new("inf + NaN");
+    let json = serde_json::to_string(&eq).unwrap();
+    let back: Equation = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.expression, eq.expression);
+    assert_eq!(
+      serde_json::to_string(&back.expression).unwrap(),
+      r#"{"op":"+","args":["inf","NaN"]}"#
+    );
+  }
 }
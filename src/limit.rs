@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+
+use super::component::{Component, Prec};
+use super::derive;
+use super::equation::Equation;
+use super::operators::Operator::*;
+use super::polynomial::snap;
+use super::rational;
+use super::utils::*;
+
+/// The side from which a limit approaches its point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+  /// Approach from values lower than the point
+  Left,
+  /// Approach from values higher than the point
+  Right,
+  /// Both one-sided limits have to exist and be equal
+  Both,
+}
+
+// Maximum amount of nested rewrites (l'hôpital, rewriting products and sums)
+const MAX_DEPTH: usize = 8;
+
+// Highest derivative used to compare the series of a numerator and denominator
+const MAX_ORDER: usize = 8;
+
+// Values smaller than this fraction of the largest number in their component are treated as zero,
+// to hide rounding errors like sin(pi)
+const ZERO: Prec = 1e-6;
+
+// Largest finite number in the component, with var replaced by the point
+fn scale(component: &Component, var: &str, point: Prec) -> Prec {
+  match component {
+    Component::Number(f) if f.is_finite() => f.abs(),
+    Component::Variable(c) if c == var && point.is_finite() => point.abs(),
+    Component::Function { values, .. } => values
+      .iter()
+      .map(|value| scale(value, var, point))
+      .fold(0.0, Prec::max),
+    _ => 0.0,
+  }
+}
+
+// Whether f, the limit of component, only differs from zero by rounding errors
+fn is_zero(f: Prec, component: &Component, var: &str, point: Prec) -> bool {
+  f.abs() <= ZERO * scale(component, var, point)
+}
+
+// Point near the limit point on the given side, used to find signs
+fn near(point: Prec, side: Prec) -> Prec {
+  if point.is_infinite() {
+    point.signum() * 1e6
+  } else {
+    point + side * 1e-3 * point.abs().max(1.0)
+  }
+}
+
+fn evaluate(component: &Component, var: &str, x: Prec) -> Option<Prec> {
+  let mut vars = HashMap::new();
+  vars.insert(var, x);
+  Equation::solve_component(&vars, component).to_float()
+}
+
+fn derivative(component: &Component, var: &str) -> Component {
+  clean(&derive::derive_partial(component, var))
+}
+
+// Sign of the component on the side of the point, arbitrarily close to the point
+// Where the component is zero at the point, the first derivative which is not zero decides the sign
+fn sign_near(component: &Component, var: &str, point: Prec, side: Prec) -> Option<Prec> {
+  if point.is_finite() {
+    let mut derived = component.clone();
+    for order in 0..=MAX_ORDER {
+      let value = match evaluate(&derived, var, point) {
+        Some(value) if value.is_finite() => value,
+        _ => break,
+      };
+      if !is_zero(value, &derived, var, point) {
+        return Some(value.signum() * if order % 2 == 1 { side } else { 1.0 });
+      }
+      derived = derivative(&derived, var);
+    }
+  }
+  evaluate(component, var, near(point, side)).map(Prec::signum)
+}
+
+// Limit of floor, round, ceil and %, which jump where their argument crosses a step
+// At a step, the side from which the argument approaches decides the value
+fn step(component: &Component, var: &str, point: Prec, side: Prec, depth: usize) -> Option<Prec> {
+  let (operator, values) = match component {
+    Component::Function { operator, values } => (operator, values),
+    _ => return None,
+  };
+  let limits = values
+    .iter()
+    .map(|x| limit_of(x, var, point, side, depth))
+    .collect::<Option<Vec<_>>>()?;
+
+  // The argument is the quotient for %, which truncates it
+  let argument = match operator {
+    Modulo => create_binary(Divide, values[0].clone(), values[1].clone()),
+    _ => values[0].clone(),
+  };
+  let inner = match operator {
+    Modulo => limits[0] / limits[1],
+    _ => limits[0],
+  };
+  let at_step = match operator {
+    Round => (inner - 0.5).fract() == 0.0,
+    _ => inner.fract() == 0.0,
+  };
+  if !inner.is_finite() || !at_step {
+    return operator.apply(&limits);
+  }
+
+  let approach = sign_near(
+    &create_binary(Subtract, argument, Component::Number(inner)),
+    var,
+    point,
+    side,
+  )?;
+  if approach == 0.0 {
+    return operator.apply(&limits);
+  }
+
+  let below = approach < 0.0;
+  Some(match operator {
+    Floor if below => inner - 1.0,
+    Ceil if !below => inner + 1.0,
+    Round if below => inner.floor(),
+    Round => inner.ceil(),
+    Modulo => {
+      // The truncated quotient just beside the integer inner
+      let truncated = match (below, inner > 0.0, inner < 0.0) {
+        (true, true, _) => inner - 1.0,
+        (false, _, true) => inner + 1.0,
+        _ => inner,
+      };
+      limits[0] - limits[1] * truncated
+    }
+    _ => inner,
+  })
+}
+
+// Compares the first non zero terms of the taylor series of numerator and denominator
+fn series(
+  numerator: &Component,
+  denominator: &Component,
+  var: &str,
+  point: Prec,
+  side: Prec,
+) -> Option<Prec> {
+  if point.is_infinite() {
+    return None;
+  }
+
+  // Order and value of the first derivative which is not zero at the point
+  let leading = |component: &Component| {
+    let mut derived = component.clone();
+    for order in 0..=MAX_ORDER {
+      let value = evaluate(&derived, var, point).filter(|f| f.is_finite())?;
+      if !is_zero(value, &derived, var, point) {
+        return Some((order, value));
+      }
+      derived = derivative(&derived, var);
+    }
+    None
+  };
+
+  let (n_order, n_value) = leading(numerator)?;
+  let (d_order, d_value) = leading(denominator)?;
+
+  // The factorials cancel out when the orders are equal
+  if n_order > d_order {
+    Some(0.0)
+  } else if n_order == d_order {
+    Some(n_value / d_value)
+  } else {
+    // (x - point) ^ (n_order - d_order) is negative on the left side for odd powers
+    let sign = if (d_order - n_order) % 2 == 1 {
+      side
+    } else {
+      1.0
+    };
+    Some(sign * (n_value / d_value).signum() * Prec::INFINITY)
+  }
+}
+
+// Replaces abs(u) by u or -u, depending on the sign of u on the side of the point
+// The derivatives of abs are not defined where u is zero, which prevents l'hôpital's rule
+fn unfold_abs(component: &Component, var: &str, point: Prec, side: Prec) -> Option<Component> {
+  match component {
+    Component::Function {
+      operator: Abs,
+      values,
+    } => {
+      let inner = unfold_abs(&values[0], var, point, side)?;
+      let sign = evaluate(&inner, var, near(point, side))?.signum();
+      Some(if sign < 0.0 {
+        create_binary(Multiply, Component::Number(-1.0), inner)
+      } else {
+        inner
+      })
+    }
+    Component::Function { operator, values } => Some(Component::Function {
+      operator: operator.clone(),
+      values: values
+        .iter()
+        .map(|x| unfold_abs(x, var, point, side))
+        .collect::<Option<_>>()?,
+    }),
+    _ => Some(component.clone()),
+  }
+}
+
+fn divide(
+  numerator: &Component,
+  denominator: &Component,
+  var: &str,
+  point: Prec,
+  side: Prec,
+  depth: usize,
+) -> Option<Prec> {
+  let n = limit_of(numerator, var, point, side, depth)?;
+  let d = limit_of(denominator, var, point, side, depth)?;
+
+  let (n_zero, d_zero) = (
+    is_zero(n, numerator, var, point),
+    is_zero(d, denominator, var, point),
+  );
+  if d_zero && !n_zero {
+    // Sign of the denominator decides the sign of the infinity
+    let sign = sign_near(denominator, var, point, side)?;
+    return Some(n.signum() * sign * Prec::INFINITY);
+  }
+
+  // 0 / 0 and inf / inf are indeterminate
+  let indeterminate = (n_zero && d_zero) || (n.is_infinite() && d.is_infinite());
+  if !indeterminate {
+    return Some(n / d);
+  }
+
+  // Apply l'hôpital's rule
+  let lhopital = || {
+    if depth >= MAX_DEPTH {
+      return None;
+    }
+    let quotient = rational::cancel(&create_binary(
+      Divide,
+      derivative(&unfold_abs(numerator, var, point, side)?, var),
+      derivative(&unfold_abs(denominator, var, point, side)?, var),
+    ));
+    limit_of(&clean(&quotient), var, point, side, depth + 1)
+  };
+
+  lhopital().or_else(|| series(numerator, denominator, var, point, side))
+}
+
+fn limit_of(
+  component: &Component,
+  var: &str,
+  point: Prec,
+  side: Prec,
+  depth: usize,
+) -> Option<Prec> {
+  let (operator, values) = match component {
+    Component::Number(f) => return Some(*f),
+    Component::Variable(c) if c == var => return Some(point),
+    Component::Function { operator, values } => (operator, values),
+    _ => return None,
+  };
+
+  let limit = match operator {
+    Divide => divide(&values[0], &values[1], var, point, side, depth)?,
+
+    Multiply => {
+      let a = limit_of(&values[0], var, point, side, depth)?;
+      let b = limit_of(&values[1], var, point, side, depth)?;
+
+      // 0 * inf, rewritten to inf / (1 / 0) or 0 / (1 / inf)
+      let a_zero = is_zero(a, &values[0], var, point);
+      if (a_zero && b.is_infinite()) || (a.is_infinite() && is_zero(b, &values[1], var, point)) {
+        if depth >= MAX_DEPTH {
+          return None;
+        }
+        let (zero, infinite) = if a_zero { (0, 1) } else { (1, 0) };
+        let reciprocal =
+          |i: usize| create_binary(Divide, Component::Number(1.0), values[i].clone());
+
+        divide(
+          &values[infinite],
+          &reciprocal(zero),
+          var,
+          point,
+          side,
+          depth + 1,
+        )
+        .or_else(|| {
+          divide(
+            &values[zero],
+            &reciprocal(infinite),
+            var,
+            point,
+            side,
+            depth + 1,
+          )
+        })?
+      } else {
+        a * b
+      }
+    }
+
+    Add | Subtract => {
+      let a = limit_of(&values[0], var, point, side, depth)?;
+      let b = limit_of(&values[1], var, point, side, depth)?;
+      let result = operator.apply(&[a, b])?;
+
+      // inf - inf, combine into a single fraction
+      if result.is_nan() && depth < MAX_DEPTH {
+        let combined = rational::together(component);
+        if combined.to_string() == component.to_string() {
+          return None;
+        }
+        limit_of(&combined, var, point, side, depth + 1)?
+      } else {
+        result
+      }
+    }
+
+    Exponent | Pow => {
+      let base = limit_of(&values[0], var, point, side, depth)?;
+      let exponent = limit_of(&values[1], var, point, side, depth)?;
+
+      // 1 ^ inf, 0 ^ 0 and inf ^ 0, rewritten to exp(exponent * ln(base))
+      let zero_exponent = is_zero(exponent, &values[1], var, point);
+      let indeterminate = ((base - 1.0).abs() < ZERO && exponent.is_infinite())
+        || (is_zero(base, &values[0], var, point) && zero_exponent)
+        || (base.is_infinite() && zero_exponent);
+      if indeterminate {
+        if depth >= MAX_DEPTH {
+          return None;
+        }
+        let power = create_binary(
+          Multiply,
+          values[1].clone(),
+          create_unary(Ln, values[0].clone()),
+        );
+        limit_of(&power, var, point, side, depth + 1)?.exp()
+      } else {
+        base.powf(exponent)
+      }
+    }
+
+    // Step functions depend on the side of the approach
+    Floor | Round | Ceil | Modulo => step(component, var, point, side, depth)?,
+
+    _ => {
+      let limits = values
+        .iter()
+        .map(|x| limit_of(x, var, point, side, depth))
+        .collect::<Option<Vec<_>>>()?;
+      operator.apply(&limits)?
+    }
+  };
+
+  // Functions of infinity without a limit, like sin(inf)
+  if limit.is_nan() {
+    None
+  } else {
+    Some(limit)
+  }
+}
+
+/// Finds the limit of a component for var approaching point, point may be infinite
+/// Returns None if the limit does not exist or could not be found
+pub fn limit(component: &Component, var: &str, point: Prec, direction: Direction) -> Option<Prec> {
+  if component.variables().iter().any(|v| v != var) {
+    return None;
+  }
+
+  // Adding zero turns -0 into 0
+  // The component has to be defined on the side of a finite point, like sqrt(x) only has a limit at 0 from the right
+  let side = |side: Prec| {
+    if point.is_finite() && evaluate(component, var, near(point, side))?.is_nan() {
+      return None;
+    }
+    limit_of(component, var, point, side, 0).map(|f| snap(f) + 0.0)
+  };
+
+  // Infinity can only be approached from one side
+  if point.is_infinite() {
+    return side(-point.signum());
+  }
+
+  match direction {
+    Direction::Left => side(-1.0),
+    Direction::Right => side(1.0),
+    Direction::Both => {
+      let (left, right) = (side(-1.0)?, side(1.0)?);
+      if left == right || (left - right).abs() < 1e-4 * left.abs().max(1.0) {
+        Some(right)
+      } else {
+        None
+      }
+    }
+  }
+}
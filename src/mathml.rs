@@ -0,0 +1,227 @@
+use super::component::{Component, Prec};
+use super::latex::{parenthesized, GREEK};
+use super::operators::{Operator, Operator::*};
+
+/// The kinds of MathML markup
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MathML {
+  /// Describes how the formula looks, for display in browsers and for screen readers
+  Presentation,
+  /// Describes what the formula means, for exchanging formulas with other computer algebra systems
+  Content,
+}
+
+// The symbols of the greek letters, in the same order as GREEK
+pub(crate) const GREEK_SYMBOLS: [char; 33] = [
+  'α', 'β', 'γ', 'δ', 'ϵ', 'ε', 'ζ', 'η', 'θ', 'ϑ', 'ι', 'κ', 'λ', 'μ', 'ν', 'ξ', 'π', 'ρ', 'σ',
+  'τ', 'υ', 'ϕ', 'φ', 'χ', 'ψ', 'ω', 'Γ', 'Δ', 'Θ', 'Λ', 'Σ', 'Φ', 'Ω',
+];
+
+// Invisible operator between a function and its argument
+const APPLY_FUNCTION: &str = "<mo>&#x2061;</mo>";
+
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+fn identifier(name: &str) -> String {
+  match GREEK.iter().position(|letter| *letter == name) {
+    Some(i) => format!("<mi>{}</mi>", GREEK_SYMBOLS[i]),
+    None => format!("<mi>{}</mi>", escape(name)),
+  }
+}
+
+fn variable(name: &str) -> String {
+  // Everything after the first underscore is a subscript
+  match name.find('_') {
+    Some(i) => {
+      let subscript = &name[i + 1..];
+      let subscript = if subscript.parse::<Prec>().is_ok() {
+        format!("<mn>{}</mn>", subscript)
+      } else {
+        identifier(subscript)
+      };
+      format!("<msub>{}{}</msub>", identifier(&name[..i]), subscript)
+    }
+    None => identifier(name),
+  }
+}
+
+fn number(f: Prec) -> String {
+  if f.is_nan() {
+    String::from("<mi>NaN</mi>")
+  } else if f.is_infinite() {
+    format!(
+      "{}<mi>&#x221E;</mi>",
+      if f < 0.0 { "<mo>-</mo>" } else { "" }
+    )
+  } else if f < 0.0 {
+    format!("<mrow><mo>-</mo><mn>{}</mn></mrow>", -f)
+  } else {
+    format!("<mn>{}</mn>", f)
+  }
+}
+
+fn fenced(open: &str, inner: String, close: &str) -> String {
+  format!("<mrow><mo>{}</mo>{}<mo>{}</mo></mrow>", open, inner, close)
+}
+
+fn presentation(component: &Component) -> String {
+  let (operator, values) = match component {
+    Component::Variable(c) => return variable(c),
+    Component::Number(f) => return number(*f),
+    Component::Function { operator, values } => (operator, values),
+    Component::End => return String::from("<mrow></mrow>"),
+  };
+
+  let operand = |i: usize| {
+    let markup = presentation(&values[i]);
+    if parenthesized(operator, i, &values[i]) {
+      fenced("(", markup, ")")
+    } else {
+      markup
+    }
+  };
+  let infix = |symbol: &str| {
+    format!(
+      "<mrow>{}<mo>{}</mo>{}</mrow>",
+      operand(0),
+      symbol,
+      operand(1)
+    )
+  };
+  let function = |name: String| {
+    let arguments: Vec<_> = values.iter().map(presentation).collect();
+    format!(
+      "<mrow>{}{}{}</mrow>",
+      name,
+      APPLY_FUNCTION,
+      fenced("(", arguments.join("<mo>,</mo>"), ")")
+    )
+  };
+
+  match (operator, values.as_slice()) {
+    (Add, [_, _]) => infix("+"),
+    (Subtract, [_, _]) => infix("-"),
+    (Multiply, [_, _]) => infix("&#x22C5;"),
+    (Modulo, [_, _]) => infix("mod"),
+    (Divide, [a, b]) => format!("<mfrac>{}{}</mfrac>", presentation(a), presentation(b)),
+    (Exponent, [_, b]) | (Pow, [_, b]) => format!("<msup>{}{}</msup>", operand(0), presentation(b)),
+    (Exp, [a]) => format!("<msup><mi>e</mi>{}</msup>", presentation(a)),
+    (Sqrt, [a]) => format!("<msqrt>{}</msqrt>", presentation(a)),
+    (Root, [a, b]) => format!("<mroot>{}{}</mroot>", presentation(a), presentation(b)),
+    (Log, [a, b]) => format!(
+      "<mrow><msub><mi>log</mi>{}</msub>{}{}</mrow>",
+      presentation(b),
+      APPLY_FUNCTION,
+      fenced("(", presentation(a), ")")
+    ),
+    (Abs, [a]) => fenced("|", presentation(a), "|"),
+    (Floor, [a]) => fenced("&#x230A;", presentation(a), "&#x230B;"),
+    (Ceil, [a]) => fenced("&#x2308;", presentation(a), "&#x2309;"),
+    (Sum, [_, k, from, to]) | (Product, [_, k, from, to]) => format!(
+      "<mrow><munderover><mo>{}</mo><mrow>{}<mo>=</mo>{}</mrow>{}</munderover>{}</mrow>",
+      if operator.compare(&Sum) {
+        "&#x2211;"
+      } else {
+        "&#x220F;"
+      },
+      presentation(k),
+      presentation(from),
+      presentation(to),
+      operand(0)
+    ),
+    _ => function(format!("<mi>{}</mi>", operator)),
+  }
+}
+
+// The content element of an operator, None if content MathML has no element for it
+fn element(operator: &Operator) -> Option<&'static str> {
+  Some(match operator {
+    Add => "plus",
+    Subtract => "minus",
+    Multiply => "times",
+    Divide => "divide",
+    Modulo => "rem",
+    Exponent | Pow => "power",
+    Sin => "sin",
+    Cos => "cos",
+    Tan => "tan",
+    Sec => "sec",
+    Csc => "csc",
+    Cot => "cot",
+    Abs => "abs",
+    Floor => "floor",
+    Ceil => "ceiling",
+    Exp => "exp",
+    Ln => "ln",
+    _ => return None,
+  })
+}
+
+fn content(component: &Component) -> String {
+  let (operator, values) = match component {
+    Component::Variable(c) => return format!("<ci>{}</ci>", escape(c)),
+    Component::Number(f) if f.is_nan() => return String::from("<notanumber/>"),
+    Component::Number(f) if f.is_infinite() && *f < 0.0 => {
+      return String::from("<apply><minus/><infinity/></apply>")
+    }
+    Component::Number(f) if f.is_infinite() => return String::from("<infinity/>"),
+    Component::Number(f) => return format!("<cn>{}</cn>", f),
+    Component::Function { operator, values } => (operator, values),
+    Component::End => return String::new(),
+  };
+
+  let arguments = |values: &[Component]| values.iter().map(content).collect::<String>();
+  match (operator, values.as_slice()) {
+    (Log, [a, b]) => format!(
+      "<apply><log/><logbase>{}</logbase>{}</apply>",
+      content(b),
+      content(a)
+    ),
+    (Sqrt, [a]) => format!("<apply><root/>{}</apply>", content(a)),
+    (Root, [a, b]) => format!(
+      "<apply><root/><degree>{}</degree>{}</apply>",
+      content(b),
+      content(a)
+    ),
+    (Round, _) => format!(
+      "<apply><csymbol cd=\"rounding1\">round</csymbol>{}</apply>",
+      arguments(values)
+    ),
+    (Sum, [body, k, from, to]) | (Product, [body, k, from, to]) => format!(
+      "<apply><{}/><bvar>{}</bvar><lowlimit>{}</lowlimit><uplimit>{}</uplimit>{}</apply>",
+      if operator.compare(&Sum) {
+        "sum"
+      } else {
+        "product"
+      },
+      content(k),
+      content(from),
+      content(to),
+      content(body)
+    ),
+    _ => match element(operator) {
+      Some(element) => format!("<apply><{}/>{}</apply>", element, arguments(values)),
+      None => format!(
+        "<apply><csymbol>{}</csymbol>{}</apply>",
+        operator,
+        arguments(values)
+      ),
+    },
+  }
+}
+
+/// Converts a component to MathML, wrapped in a math element
+pub fn to_mathml(component: &Component, markup: MathML) -> String {
+  format!(
+    "<math xmlns=\"http://www.w3.org/1998/Math/MathML\">{}</math>",
+    match markup {
+      MathML::Presentation => presentation(component),
+      MathML::Content => content(component),
+    }
+  )
+}
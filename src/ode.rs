@@ -0,0 +1,282 @@
+use super::component::Prec;
+use super::equation::Equation;
+use super::evaluate::Compiled;
+
+// Maximum amount of steps before giving up
+const MAX_STEPS: usize = 100_000;
+
+// Dormand-Prince coefficients, the 5th order solution is used and the 4th order one estimates the error
+const C: [Prec; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+const A: [[Prec; 6]; 7] = [
+  [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+  [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+  [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+  [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+  [
+    19372.0 / 6561.0,
+    -25360.0 / 2187.0,
+    64448.0 / 6561.0,
+    -212.0 / 729.0,
+    0.0,
+    0.0,
+  ],
+  [
+    9017.0 / 3168.0,
+    -355.0 / 33.0,
+    46732.0 / 5247.0,
+    49.0 / 176.0,
+    -5103.0 / 18656.0,
+    0.0,
+  ],
+  [
+    35.0 / 384.0,
+    0.0,
+    500.0 / 1113.0,
+    125.0 / 192.0,
+    -2187.0 / 6784.0,
+    11.0 / 84.0,
+  ],
+];
+const B4: [Prec; 7] = [
+  5179.0 / 57600.0,
+  0.0,
+  7571.0 / 16695.0,
+  393.0 / 640.0,
+  -92097.0 / 339_200.0,
+  187.0 / 2100.0,
+  1.0 / 40.0,
+];
+
+/// The available methods to solve ordinary differential equations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Solver {
+  /// Classic 4th order Runge-Kutta with a fixed step size
+  Rk4 { step: Prec },
+  /// Adaptive 5th order Dormand-Prince, the step size is chosen to keep the error per step below the tolerance
+  DormandPrince { tolerance: Prec },
+}
+
+/// The solution of an ordinary differential equation, a time series of states
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+  /// The times at which the solver stepped, from the start to the end time
+  pub times: Vec<Prec>,
+  /// The values of the variables at every time, in the order of the variables
+  pub states: Vec<Vec<Prec>>,
+  // The derivatives at every time, used for interpolation
+  derivatives: Vec<Vec<Prec>>,
+}
+
+impl Solution {
+  /// Interpolates the state at any time between the start and the end time with cubic hermite interpolation
+  /// Returns None if the time is outside of the solution
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::{Equation, Solver};
+  ///
+  /// let solution = Equation::new("y").solve_ode("y", "t", 0.0, 1.0, 1.0, Solver::DormandPrince { tolerance: 1e-6 }).unwrap();
+  ///
+  /// assert!((solution.at(0.5).unwrap()[0] - 0.5f32.exp()).abs() < 1e-4);
+  /// ```
+  pub fn at(&self, t: Prec) -> Option<Vec<Prec>> {
+    let (first, last) = (*self.times.first()?, *self.times.last()?);
+    if t < first.min(last) || t > first.max(last) {
+      return None;
+    }
+
+    // The times are sorted in the direction of integration
+    let i = self
+      .times
+      .windows(2)
+      .position(|w| (w[0] <= t && t <= w[1]) || (w[1] <= t && t <= w[0]))
+      .unwrap_or(0);
+    if self.times.len() == 1 {
+      return Some(self.states[0].clone());
+    }
+
+    let h = self.times[i + 1] - self.times[i];
+    let s = (t - self.times[i]) / h;
+    let (h00, h10) = (
+      2.0 * s.powi(3) - 3.0 * s * s + 1.0,
+      s.powi(3) - 2.0 * s * s + s,
+    );
+    let (h01, h11) = (-2.0 * s.powi(3) + 3.0 * s * s, s.powi(3) - s * s);
+    Some(
+      (0..self.states[i].len())
+        .map(|j| {
+          h00 * self.states[i][j]
+            + h10 * h * self.derivatives[i][j]
+            + h01 * self.states[i + 1][j]
+            + h11 * h * self.derivatives[i + 1][j]
+        })
+        .collect(),
+    )
+  }
+}
+
+struct System {
+  equations: Vec<Compiled>,
+}
+
+impl System {
+  // Derivatives of all variables at time t and state y
+  fn derivatives(&self, t: Prec, y: &[Prec]) -> Vec<Prec> {
+    let mut values = y.to_vec();
+    values.push(t);
+    self.equations.iter().map(|eq| eq.call(&values)).collect()
+  }
+}
+
+// y + h * sum(weights * k)
+fn add_scaled(y: &[Prec], h: Prec, weights: &[Prec], k: &[Vec<Prec>]) -> Vec<Prec> {
+  (0..y.len())
+    .map(|j| y[j] + h * weights.iter().zip(k).map(|(w, k)| w * k[j]).sum::<Prec>())
+    .collect()
+}
+
+fn rk4(system: &System, t: Prec, y: &[Prec], h: Prec) -> Vec<Prec> {
+  let k1 = system.derivatives(t, y);
+  let k2 = system.derivatives(
+    t + h / 2.0,
+    &add_scaled(y, h / 2.0, &[1.0], std::slice::from_ref(&k1)),
+  );
+  let k3 = system.derivatives(
+    t + h / 2.0,
+    &add_scaled(y, h / 2.0, &[1.0], std::slice::from_ref(&k2)),
+  );
+  let k4 = system.derivatives(t + h, &add_scaled(y, h, &[1.0], std::slice::from_ref(&k3)));
+  add_scaled(y, h / 6.0, &[1.0, 2.0, 2.0, 1.0], &[k1, k2, k3, k4])
+}
+
+// A single Dormand-Prince step, returns the new state, its derivatives and the error estimate per variable
+fn dormand_prince(
+  system: &System,
+  t: Prec,
+  y: &[Prec],
+  dy: &[Prec],
+  h: Prec,
+) -> (Vec<Prec>, Vec<Prec>, Vec<Prec>) {
+  let mut k = vec![dy.to_vec()];
+  for stage in 1..7 {
+    let state = add_scaled(y, h, &A[stage][..stage], &k);
+    k.push(system.derivatives(t + C[stage] * h, &state));
+  }
+
+  // The last stage is evaluated at the new state, so it is reused as the next first stage
+  let next = add_scaled(y, h, &A[6], &k[..6]);
+  let fourth = add_scaled(y, h, &B4, &k);
+  let error = next
+    .iter()
+    .zip(&fourth)
+    .map(|(a, b)| (a - b).abs())
+    .collect();
+  (next, k.swap_remove(6), error)
+}
+
+/// Solves a system of ordinary differential equations, every equation is the derivative of a variable in vars
+/// The equations may contain the variables and the time variable, starting at t0 with the initial values
+/// Returns None if an equation contains other variables or the solution is not finite
+pub fn solve_ode(
+  equations: &[Equation],
+  vars: &[&str],
+  time: &str,
+  t0: Prec,
+  initial: &[Prec],
+  end: Prec,
+  solver: Solver,
+) -> Option<Solution> {
+  if equations.len() != vars.len() || initial.len() != vars.len() {
+    return None;
+  }
+
+  let mut names = vars.to_vec();
+  names.push(time);
+  let system = System {
+    equations: equations
+      .iter()
+      .map(|eq| Compiled::new(&eq.expression, &names))
+      .collect::<Option<Vec<_>>>()?,
+  };
+
+  let direction = (end - t0).signum();
+  let span = (end - t0).abs();
+  let mut t = t0;
+  let mut y = initial.to_vec();
+  let mut dy = system.derivatives(t, &y);
+  let mut solution = Solution {
+    times: vec![t],
+    states: vec![y.clone()],
+    derivatives: vec![dy.clone()],
+  };
+
+  let mut h = match solver {
+    Solver::Rk4 { step } => step.abs() * direction,
+    Solver::DormandPrince { .. } => span / 100.0 * direction,
+  };
+  if h == 0.0 && span != 0.0 {
+    return None;
+  }
+
+  let mut steps = 0;
+  while (end - t) * direction > 0.0 {
+    steps += 1;
+    if steps > MAX_STEPS {
+      return None;
+    }
+    // Don't step past the end
+    let last = (t + h - end) * direction >= 0.0;
+    let step = if last { end - t } else { h };
+
+    match solver {
+      Solver::Rk4 { .. } => {
+        y = rk4(&system, t, &y, step);
+        // Multiplying prevents rounding errors from adding up over many steps
+        t = if last { end } else { t0 + steps as Prec * h };
+        dy = system.derivatives(t, &y);
+      }
+      Solver::DormandPrince { tolerance } => {
+        let (next, next_dy, error) = dormand_prince(&system, t, &y, &dy, step);
+
+        // Largest error relative to the tolerance, mixed absolute and relative
+        let ratio = error
+          .iter()
+          .zip(y.iter().zip(&next))
+          .map(|(e, (a, b))| e / (tolerance * (1.0 + a.abs().max(b.abs()))))
+          .fold(0.0, Prec::max);
+        if !ratio.is_finite() {
+          return None;
+        }
+
+        if ratio <= 1.0 {
+          t = if last { end } else { t + step };
+          y = next;
+          dy = next_dy;
+        }
+
+        // Grow or shrink the step for the next attempt
+        let factor = if ratio == 0.0 {
+          5.0
+        } else {
+          (0.9 * ratio.powf(-0.2)).clamp(0.2, 5.0)
+        };
+        h = step * factor;
+        if h.abs() < span * Prec::EPSILON {
+          return None;
+        }
+        if ratio > 1.0 {
+          continue;
+        }
+      }
+    }
+
+    if y.iter().any(|f| !f.is_finite()) {
+      return None;
+    }
+    solution.times.push(t);
+    solution.states.push(y.clone());
+    solution.derivatives.push(dy.clone());
+  }
+
+  Some(solution)
+}
@@ -1,12 +1,14 @@
 use std::fmt;
 
+use super::component::Prec;
+
 /// These are all the functions/operators that can be used in an equation.
 ///
 /// The operators are: Add (+), Subtract (-), Multiply (*), Divide(/), Modulo (%), Exponent(^).
 ///
 /// All syntax is the same for the other functions but lowercase.
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Operator {
   Add,      // +
   Subtract, // -
@@ -30,6 +32,8 @@ pub enum Operator {
   Exp,      // exp(n)
   Ln,       // ln(n)
   Sqrt,     // sqrt(n)
+  Sum,      // sum(expression, k, from, to)
+  Product,  // prod(expression, k, from, to)
   Error,
 }
 
@@ -60,6 +64,8 @@ impl From<&str> for Operator {
       "exp" => Exp,
       "ln" => Ln,
       "sqrt" => Sqrt,
+      "sum" => Sum,
+      "prod" => Product,
       _ => Error,
     }
   }
@@ -76,6 +82,85 @@ impl Operator {
   pub(crate) fn compare(&self, other: &Operator) -> bool {
     std::mem::discriminant(self) == std::mem::discriminant(other)
   }
+
+  // Applies operator to numbers, returns None if the amount of values does not match the operator
+  pub(crate) fn apply(&self, values: &[Prec]) -> Option<Prec> {
+    match *values {
+      // Unary operators
+      [f] => Some(match self {
+        Sin => f.sin(),
+        Cos => f.cos(),
+        Tan => f.tan(),
+        Sec => 1.0 / f.cos(),
+        Csc => 1.0 / f.sin(),
+        Cot => 1.0 / f.tan(),
+        Abs => f.abs(),
+        Floor => f.floor(),
+        Round => f.round(),
+        Ceil => f.ceil(),
+        Exp => f.exp(),
+        Ln => f.ln(),
+        Sqrt => f.sqrt(),
+        _ => return None,
+      }),
+
+      // Binary operators
+      [f1, f2] => Some(match self {
+        Add => f1 + f2,
+        Subtract => f1 - f2,
+        Multiply => f1 * f2,
+        Divide => f1 / f2,
+        Modulo => f1 % f2,
+        Exponent | Pow => f1.powf(f2),
+        Log => f1.log(f2),
+        Root => f1.powf(1.0 / f2),
+        _ => return None,
+      }),
+
+      _ => None,
+    }
+  }
+
+  // Partial derivatives of the operator with respect to each value, at the given values
+  // Returns None if the amount of values does not match the operator
+  pub(crate) fn partials(&self, values: &[Prec]) -> Option<Vec<Prec>> {
+    match *values {
+      [f] => Some(vec![match self {
+        Sin => f.cos(),
+        Cos => -f.sin(),
+        Tan => 1.0 / (f.cos() * f.cos()),
+        Sec => f.tan() / f.cos(),
+        Csc => -1.0 / (f.sin() * f.tan()),
+        Cot => -1.0 / (f.sin() * f.sin()),
+        Abs => f.signum(),
+        Floor | Round | Ceil => 0.0,
+        Exp => f.exp(),
+        Ln => 1.0 / f,
+        Sqrt => 0.5 / f.sqrt(),
+        _ => return None,
+      }]),
+
+      [f1, f2] => Some(match self {
+        Add => vec![1.0, 1.0],
+        Subtract => vec![1.0, -1.0],
+        Multiply => vec![f2, f1],
+        Divide => vec![1.0 / f2, -f1 / (f2 * f2)],
+        // f1 % f2 equals f1 - trunc(f1 / f2) * f2
+        Modulo => vec![1.0, -(f1 / f2).trunc()],
+        Exponent | Pow => vec![f2 * f1.powf(f2 - 1.0), f1.powf(f2) * f1.ln()],
+        // log(f1, f2) equals ln(f1) / ln(f2)
+        Log => vec![1.0 / (f1 * f2.ln()), -f1.ln() / (f2 * f2.ln() * f2.ln())],
+        // root(f1, f2) equals f1 ^ (1 / f2)
+        Root => vec![
+          f1.powf(1.0 / f2 - 1.0) / f2,
+          -f1.powf(1.0 / f2) * f1.ln() / (f2 * f2),
+        ],
+        _ => return None,
+      }),
+
+      _ => None,
+    }
+  }
 }
 
 impl fmt::Display for Operator {
@@ -88,7 +173,7 @@ impl fmt::Display for Operator {
       Modulo => "%",
       Exponent => "^",
       Pow => "pow",
-      Log => "logab",
+      Log => "log",
       Sin => "sin",
       Cos => "cos",
       Tan => "tan",
@@ -103,6 +188,8 @@ impl fmt::Display for Operator {
       Exp => "exp",
       Ln => "ln",
       Sqrt => "sqrt",
+      Sum => "sum",
+      Product => "prod",
       Error => "error",
     })
   }
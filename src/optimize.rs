@@ -0,0 +1,343 @@
+use super::component::Prec;
+use super::equation::Equation;
+use super::tape::Tape;
+
+// Maximum amount of iterations before giving up
+const MAX_ITERATIONS: usize = 10_000;
+
+// Converged when the gradient or the simplex is smaller than this
+const TOLERANCE: Prec = 1e-5;
+
+// The tape is recorded with the variables of the start values, so every point has a value for each of them
+const POINT: &str = "point has a value for every variable of the tape";
+
+/// The available methods for numerical minimization
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+  /// Steepest descent with a backtracking line search, simple but slow for narrow valleys
+  GradientDescent,
+  /// Quasi-newton method which builds an approximation of the inverse hessian, fast for smooth functions
+  Bfgs,
+  /// Derivative free simplex method, works for functions without a (useful) derivative
+  NelderMead,
+}
+
+/// The outcome of a numerical minimization
+#[derive(Debug, Clone, PartialEq)]
+pub struct Minimum {
+  /// The variables at the minimum, in the order of the start values
+  pub point: Vec<Prec>,
+  /// The value of the equation at the minimum
+  pub value: Prec,
+  /// The amount of iterations of the method
+  pub iterations: usize,
+  /// The amount of times the equation was evaluated
+  pub evaluations: usize,
+  /// Whether the tolerance was reached before the maximum amount of iterations
+  pub converged: bool,
+}
+
+struct Problem {
+  tape: Tape,
+  bounds: Vec<(Prec, Prec)>,
+  evaluations: usize,
+}
+
+impl Problem {
+  fn clamp(&self, point: &mut [Prec]) {
+    for (x, (low, high)) in point.iter_mut().zip(&self.bounds) {
+      *x = x.max(*low).min(*high);
+    }
+  }
+
+  fn value(&mut self, point: &[Prec]) -> Prec {
+    self.evaluations += 1;
+    let value = self.tape.value(point).expect(POINT);
+    if value.is_nan() {
+      Prec::INFINITY
+    } else {
+      value
+    }
+  }
+
+  // Gradient with the components which push a variable out of its bounds set to zero
+  fn gradient(&mut self, point: &[Prec]) -> (Prec, Vec<Prec>) {
+    self.evaluations += 1;
+    let (value, mut gradient) = self.tape.gradient(point).expect(POINT);
+    for ((g, x), (low, high)) in gradient.iter_mut().zip(point).zip(&self.bounds) {
+      if (*x <= *low && *g > 0.0) || (*x >= *high && *g < 0.0) {
+        *g = 0.0;
+      }
+    }
+    (value, gradient)
+  }
+
+  // Backtracking line search along direction until the armijo condition holds
+  // Returns the new point and its value, or None if no step decreases the value
+  fn line_search(
+    &mut self,
+    point: &[Prec],
+    value: Prec,
+    gradient: &[Prec],
+    direction: &[Prec],
+  ) -> Option<(Vec<Prec>, Prec)> {
+    let slope = dot(gradient, direction);
+    let mut step = 1.0;
+    for _ in 0..50 {
+      let mut next: Vec<_> = point
+        .iter()
+        .zip(direction)
+        .map(|(x, d)| x + step * d)
+        .collect();
+      self.clamp(&mut next);
+      let next_value = self.value(&next);
+      if next_value <= value + 1e-4 * step * slope && next_value < value {
+        return Some((next, next_value));
+      }
+      step /= 2.0;
+    }
+    None
+  }
+}
+
+fn dot(a: &[Prec], b: &[Prec]) -> Prec {
+  a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn norm(a: &[Prec]) -> Prec {
+  dot(a, a).sqrt()
+}
+
+fn gradient_descent(problem: &mut Problem, mut point: Vec<Prec>) -> (Vec<Prec>, usize, bool) {
+  for iteration in 0..MAX_ITERATIONS {
+    let (value, gradient) = problem.gradient(&point);
+    if norm(&gradient) < TOLERANCE {
+      return (point, iteration, true);
+    }
+
+    let direction: Vec<_> = gradient.iter().map(|g| -g).collect();
+    match problem.line_search(&point, value, &gradient, &direction) {
+      Some((next, _)) => point = next,
+      // No step decreases the value, which is a minimum within precision if the gradient is small
+      None => return (point, iteration, norm(&gradient) < TOLERANCE.sqrt()),
+    }
+  }
+  (point, MAX_ITERATIONS, false)
+}
+
+fn bfgs(problem: &mut Problem, mut point: Vec<Prec>) -> (Vec<Prec>, usize, bool) {
+  let n = point.len();
+  let identity = |i: usize, j: usize| if i == j { 1.0 } else { 0.0 };
+  // Approximation of the inverse hessian
+  let mut inverse: Vec<Vec<Prec>> = (0..n)
+    .map(|i| (0..n).map(|j| identity(i, j)).collect())
+    .collect();
+  let (mut value, mut gradient) = problem.gradient(&point);
+
+  for iteration in 0..MAX_ITERATIONS {
+    if norm(&gradient) < TOLERANCE {
+      return (point, iteration, true);
+    }
+
+    let mut direction: Vec<Prec> = inverse.iter().map(|row| -dot(row, &gradient)).collect();
+    // Restart with steepest descent when the approximation stops pointing downhill
+    if dot(&direction, &gradient) >= 0.0 {
+      inverse = (0..n)
+        .map(|i| (0..n).map(|j| identity(i, j)).collect())
+        .collect();
+      direction = gradient.iter().map(|g| -g).collect();
+    }
+
+    let (next, next_value) = match problem.line_search(&point, value, &gradient, &direction) {
+      Some(next) => next,
+      None => return (point, iteration, norm(&gradient) < TOLERANCE.sqrt()),
+    };
+    let (_, next_gradient) = problem.gradient(&next);
+
+    // Update the inverse hessian with the step s and the change in gradient y
+    let s: Vec<_> = next.iter().zip(&point).map(|(a, b)| a - b).collect();
+    let y: Vec<_> = next_gradient
+      .iter()
+      .zip(&gradient)
+      .map(|(a, b)| a - b)
+      .collect();
+    let sy = dot(&s, &y);
+    if sy > Prec::EPSILON {
+      let hy: Vec<_> = inverse.iter().map(|row| dot(row, &y)).collect();
+      let yhy = dot(&y, &hy);
+      for i in 0..n {
+        for j in 0..n {
+          inverse[i][j] +=
+            (sy + yhy) * s[i] * s[j] / (sy * sy) - (hy[i] * s[j] + s[i] * hy[j]) / sy;
+        }
+      }
+    }
+
+    point = next;
+    value = next_value;
+    gradient = next_gradient;
+  }
+  (point, MAX_ITERATIONS, false)
+}
+
+fn nelder_mead(problem: &mut Problem, point: Vec<Prec>) -> (Vec<Prec>, usize, bool) {
+  let n = point.len();
+
+  // Initial simplex, the start point with a step along every axis
+  let mut simplex = vec![point.clone()];
+  for i in 0..n {
+    let mut vertex = point.clone();
+    vertex[i] += 0.1 * vertex[i].abs().max(1.0);
+    problem.clamp(&mut vertex);
+    if vertex[i] == point[i] {
+      vertex[i] -= 0.1 * vertex[i].abs().max(1.0);
+      problem.clamp(&mut vertex);
+    }
+    simplex.push(vertex);
+  }
+  let mut values: Vec<_> = simplex.iter().map(|x| problem.value(x)).collect();
+
+  // Point along the line from the centroid through the worst vertex
+  let along = |problem: &Problem, centroid: &[Prec], worst: &[Prec], factor: Prec| {
+    let mut x: Vec<_> = centroid
+      .iter()
+      .zip(worst)
+      .map(|(c, w)| c + factor * (w - c))
+      .collect();
+    problem.clamp(&mut x);
+    x
+  };
+
+  for iteration in 0..MAX_ITERATIONS {
+    let mut order: Vec<_> = (0..=n).collect();
+    order.sort_by(|a, b| values[*a].partial_cmp(&values[*b]).unwrap());
+    simplex = order.iter().map(|i| simplex[*i].clone()).collect();
+    values = order.iter().map(|i| values[*i]).collect();
+
+    let spread = values[n] - values[0];
+    let size = simplex
+      .iter()
+      .map(|x| {
+        x.iter()
+          .zip(&simplex[0])
+          .map(|(a, b)| (a - b).abs())
+          .fold(0.0, Prec::max)
+      })
+      .fold(0.0, Prec::max);
+    if spread.abs() <= TOLERANCE * values[0].abs().max(1.0) && size <= TOLERANCE.sqrt() {
+      return (simplex.swap_remove(0), iteration, true);
+    }
+
+    let centroid: Vec<_> = (0..n)
+      .map(|j| simplex[..n].iter().map(|x| x[j]).sum::<Prec>() / n as Prec)
+      .collect();
+
+    let reflected = along(problem, &centroid, &simplex[n], -1.0);
+    let reflected_value = problem.value(&reflected);
+
+    if reflected_value < values[0] {
+      let expanded = along(problem, &centroid, &simplex[n], -2.0);
+      let expanded_value = problem.value(&expanded);
+      if expanded_value < reflected_value {
+        simplex[n] = expanded;
+        values[n] = expanded_value;
+      } else {
+        simplex[n] = reflected;
+        values[n] = reflected_value;
+      }
+    } else if reflected_value < values[n - 1] {
+      simplex[n] = reflected;
+      values[n] = reflected_value;
+    } else {
+      let contracted = along(problem, &centroid, &simplex[n], 0.5);
+      let contracted_value = problem.value(&contracted);
+      if contracted_value < values[n] {
+        simplex[n] = contracted;
+        values[n] = contracted_value;
+      } else {
+        // Shrink every vertex towards the best vertex
+        for i in 1..=n {
+          let best = simplex[0].clone();
+          simplex[i] = along(problem, &best, &simplex[i], 0.5);
+          values[i] = problem.value(&simplex[i]);
+        }
+      }
+    }
+  }
+  (simplex.swap_remove(0), MAX_ITERATIONS, false)
+}
+
+/// Minimizes an equation starting at the given variable values, every variable in the equation has to be given
+/// The gradient methods use reverse mode automatic differentiation
+/// Returns None if the equation contains other variables or is not finite at the start
+///
+/// # Examples
+/// ```
+/// use calculi::{minimize, Equation, Method};
+///
+/// let eq = Equation::new("x ^ 2 + x * y + y ^ 2 - 3 * x - 3 * y");
+/// let minimum = minimize(&eq, vec![("x", -1.0), ("y", 2.0)], Method::Bfgs).unwrap();
+///
+/// assert!(minimum.converged);
+/// assert!((minimum.point[0] - 1.0).abs() < 1e-2 && (minimum.point[1] - 1.0).abs() < 1e-2);
+/// ```
+pub fn minimize<'a>(
+  equation: &Equation,
+  start: impl IntoIterator<Item = (&'a str, Prec)>,
+  method: Method,
+) -> Option<Minimum> {
+  let start: Vec<_> = start.into_iter().collect();
+  let bounds = vec![(Prec::NEG_INFINITY, Prec::INFINITY); start.len()];
+  minimize_within(equation, start, &bounds, method)
+}
+
+/// Minimizes an equation like minimize, keeping every variable within its (low, high) bounds
+/// The bounds are in the order of the start values, returns None if a low bound is above its high bound
+///
+/// # Examples
+/// ```
+/// use calculi::{minimize_within, Equation, Method};
+///
+/// let eq = Equation::new("x ^ 2 + y ^ 2");
+/// let minimum = minimize_within(&eq, vec![("x", 3.0), ("y", 3.0)], &[(1.0, 5.0), (-5.0, 5.0)], Method::NelderMead).unwrap();
+///
+/// assert!((minimum.point[0] - 1.0).abs() < 1e-2 && minimum.point[1].abs() < 1e-2);
+/// assert!((minimum.value - 1.0).abs() < 1e-2);
+/// assert_eq!(minimize_within(&eq, vec![("x", 3.0), ("y", 3.0)], &[(5.0, 1.0), (-5.0, 5.0)], Method::Bfgs), None);
+/// ```
+pub fn minimize_within<'a>(
+  equation: &Equation,
+  start: impl IntoIterator<Item = (&'a str, Prec)>,
+  bounds: &[(Prec, Prec)],
+  method: Method,
+) -> Option<Minimum> {
+  let (vars, mut point): (Vec<_>, Vec<_>) = start.into_iter().unzip();
+  // NaN bounds are rejected too
+  if bounds.len() != vars.len() || !bounds.iter().all(|(low, high)| low <= high) {
+    return None;
+  }
+
+  let mut problem = Problem {
+    tape: equation.tape(&vars)?,
+    bounds: bounds.to_vec(),
+    evaluations: 0,
+  };
+  problem.clamp(&mut point);
+  if !problem.value(&point).is_finite() {
+    return None;
+  }
+
+  let (point, iterations, converged) = match method {
+    Method::GradientDescent => gradient_descent(&mut problem, point),
+    Method::Bfgs => bfgs(&mut problem, point),
+    Method::NelderMead => nelder_mead(&mut problem, point),
+  };
+
+  Some(Minimum {
+    value: problem.value(&point),
+    point,
+    iterations,
+    evaluations: problem.evaluations,
+    converged,
+  })
+}
@@ -14,15 +14,12 @@ enum Simplified {
 }
 
 fn is_operator(c: char) -> bool {
-  match c {
-    '+' | '-' | '*' | '/' | '%' | '^' => true,
-    _ => false,
-  }
+  matches!(c, '+' | '-' | '*' | '/' | '%' | '^')
 }
 
 // Checks if character is a floating point digit
 fn is_digit(c: char) -> bool {
-  (c >= '0' && c <= '9') || c == '.'
+  c.is_ascii_digit() || c == '.'
 }
 
 // Parses a component out of a peekable iterator of characters
@@ -31,11 +28,25 @@ fn parse_component(chars: &mut Peekable<Chars>) -> Component {
   let mut maybe_var = String::new();
 
   while let Some(c) = chars.peek() {
-    if is_digit(*c) {
+    // Digits after a letter are part of a variable name, like x1
+    if is_digit(*c) && maybe_var.is_empty() {
       maybe_num.push(*c);
     } else if !maybe_num.is_empty() {
       break;
     }
+    // A minus sign before a value negates it, after applying exponents so -2 ^ 2 is -4
+    else if *c == '-' && maybe_var.is_empty() {
+      chars.next();
+      let mut value = parse_component(chars);
+      if let Some('^') = chars.peek() {
+        value = parse_binary(chars, operators::get_precedence(Some(&'^')), value);
+      }
+      return match value {
+        Component::Number(f) => Component::Number(-f),
+        Component::End => Component::End,
+        value => create_binary(Multiply, Component::Number(-1.0), value),
+      };
+    }
     // Parse parenthesis
     else if *c == '(' {
       chars.next();
@@ -64,6 +75,9 @@ fn parse_component(chars: &mut Peekable<Chars>) -> Component {
 
       // Normal parenthesis
       } else {
+        if let Some(')') = chars.peek() {
+          chars.next();
+        }
         return first;
       }
     } else if !is_operator(*c) && *c != ',' && *c != ')' {
@@ -101,7 +115,10 @@ pub fn simplify(component: Component) -> Component {
           Simplified::None => create_binary(operator, left, right),
         }
       } else {
-        Component::Function { operator, values }
+        Component::Function {
+          operator,
+          values: values.into_iter().map(simplify).collect(),
+        }
       }
     }
     _ => component,
@@ -156,9 +173,9 @@ fn simplify_binary(operator: &Operator, left: &Component, right: &Component) ->
 fn parse_binary(chars: &mut Peekable<Chars>, prev_prec: i8, left: Component) -> Component {
   let mut left = left;
   loop {
-    // Skips current character if it is not an operator
+    // Skips current character if it is not an operator, commas separate function parameters
     if let Some(c) = chars.peek() {
-      if !is_operator(*c) && *c != ')' {
+      if !is_operator(*c) && *c != ')' && *c != ',' {
         chars.next();
       }
     }
@@ -173,14 +190,15 @@ fn parse_binary(chars: &mut Peekable<Chars>, prev_prec: i8, left: Component) ->
     }
 
     let c = *c.unwrap();
+    chars.next();
     let mut right = parse_component(chars);
 
-
     let new_prec = operators::get_precedence(chars.peek());
 
     // Create new binary component if current operator precedence is higher than the previous one
-    if prec < new_prec {
-      right = parse_binary(chars, prec + 1, right);
+    // ^ is right associative, so a ^ b ^ c is a ^ (b ^ c)
+    if prec < new_prec || (c == '^' && new_prec == prec) {
+      right = parse_binary(chars, if c == '^' { prec } else { prec + 1 }, right);
       if let Component::End = right {
         return Component::End;
       }
@@ -189,14 +207,12 @@ fn parse_binary(chars: &mut Peekable<Chars>, prev_prec: i8, left: Component) ->
     let operator = Operator::from(c);
 
     // Check some constant expressions to attempt to shorten equation
-    match simplify_binary(&operator, &left, &right) {
-      Simplified::Left => return left,
-      Simplified::Right => return right,
-      Simplified::Component(component) => return component,
-      Simplified::None => (),
-    }
-
-    left = create_binary(operator, left, right);
+    left = match simplify_binary(&operator, &left, &right) {
+      Simplified::Left => left,
+      Simplified::Right => right,
+      Simplified::Component(component) => component,
+      Simplified::None => create_binary(operator, left, right),
+    };
   }
 }
 
@@ -0,0 +1,1083 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use super::component::{Component, Prec};
+use super::operators::Operator::*;
+use super::utils::*;
+
+// Float coefficients smaller than this are treated as rounding noise by the root and gcd heuristics
+pub(crate) const EPSILON: Prec = 1e-4;
+
+// Highest integer power which is expanded when a component is converted to a polynomial
+const MAX_POWER: Prec = 1000.0;
+
+/// The numbers a [Polynomial](struct.Polynomial.html) can use as coefficients.
+///
+/// Implemented for floats and exact [Rational](struct.Rational.html) numbers.
+pub trait Coefficient:
+  Clone
+  + fmt::Debug
+  + PartialEq
+  + Add<Output = Self>
+  + Sub<Output = Self>
+  + Mul<Output = Self>
+  + Div<Output = Self>
+  + Neg<Output = Self>
+{
+  fn zero() -> Self;
+  fn one() -> Self;
+  fn is_zero(&self) -> bool;
+  /// Whether the coefficient is small enough to be rounding noise, which the gcd and root finding ignore
+  fn is_negligible(&self) -> bool {
+    self.is_zero()
+  }
+  /// Returns None if the float can not be represented by this coefficient type
+  fn from_float(f: Prec) -> Option<Self>;
+  fn to_float(&self) -> Prec;
+}
+
+impl Coefficient for Prec {
+  fn zero() -> Self {
+    0.0
+  }
+
+  fn one() -> Self {
+    1.0
+  }
+
+  fn is_zero(&self) -> bool {
+    *self == 0.0
+  }
+
+  fn is_negligible(&self) -> bool {
+    self.abs() <= EPSILON
+  }
+
+  fn from_float(f: Prec) -> Option<Self> {
+    if f.is_finite() {
+      Some(f)
+    } else {
+      None
+    }
+  }
+
+  fn to_float(&self) -> Prec {
+    *self
+  }
+}
+
+fn integer_gcd(a: i128, b: i128) -> i128 {
+  if b == 0 {
+    a.abs()
+  } else {
+    integer_gcd(b, a % b)
+  }
+}
+
+/// An exact fraction, used as polynomial coefficient to prevent rounding errors
+///
+/// The numerator and denominator are i64, results are computed with i128 and reduced before they are stored.
+/// When a reduced result does not fit in i64 or is divided by zero, the operators give an invalid fraction,
+/// which is shown as NaN, converts to a NaN float and stays invalid in further operations like a NaN float.
+/// The checked methods return None instead.
+///
+/// # Examples
+/// ```
+/// use calculi::Rational;
+///
+/// let half = Rational::new(2, 4);
+///
+/// assert_eq!(half.to_string(), "1/2");
+///
+/// let big = Rational::new(i64::MAX, 1);
+///
+/// assert_eq!(big.checked_mul(half).unwrap().to_string(), "9223372036854775807/2");
+/// assert_eq!(big.checked_add(Rational::new(1, 1)), None);
+/// assert_eq!(big.checked_mul(Rational::new(2, 1)), None);
+/// assert_eq!((big * Rational::new(2, 1) - big).to_string(), "NaN");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rational {
+  numerator: i64,
+  denominator: i64,
+}
+
+// The result of operations which overflow, the only fraction with a zero denominator
+const INVALID: Rational = Rational {
+  numerator: 0,
+  denominator: 0,
+};
+
+impl Rational {
+  /// Creates a new reduced fraction, panics if the denominator is 0
+  pub fn new(numerator: i64, denominator: i64) -> Rational {
+    assert!(denominator != 0, "denominator of a rational can not be 0");
+
+    Rational::reduce(numerator as i128, denominator as i128).expect("rational overflow")
+  }
+
+  // Reduces the fraction, returns None if it does not fit in i64 or the denominator is 0
+  fn reduce(numerator: i128, denominator: i128) -> Option<Rational> {
+    if denominator == 0 {
+      return None;
+    }
+
+    let gcd = integer_gcd(numerator, denominator).max(1) * denominator.signum();
+    Some(Rational {
+      numerator: i64::try_from(numerator / gcd).ok()?,
+      denominator: i64::try_from(denominator / gcd).ok()?,
+    })
+  }
+
+  pub fn numerator(&self) -> i64 {
+    self.numerator
+  }
+
+  pub fn denominator(&self) -> i64 {
+    self.denominator
+  }
+
+  /// Adds two fractions, returns None if the result does not fit in i64
+  pub fn checked_add(self, other: Rational) -> Option<Rational> {
+    Rational::reduce(
+      self.numerator as i128 * other.denominator as i128
+        + other.numerator as i128 * self.denominator as i128,
+      self.denominator as i128 * other.denominator as i128,
+    )
+  }
+
+  /// Subtracts two fractions, returns None if the result does not fit in i64
+  pub fn checked_sub(self, other: Rational) -> Option<Rational> {
+    self.checked_add(other.checked_neg()?)
+  }
+
+  /// Multiplies two fractions, returns None if the result does not fit in i64
+  pub fn checked_mul(self, other: Rational) -> Option<Rational> {
+    Rational::reduce(
+      self.numerator as i128 * other.numerator as i128,
+      self.denominator as i128 * other.denominator as i128,
+    )
+  }
+
+  /// Divides two fractions, returns None if the result does not fit in i64 or other is 0
+  pub fn checked_div(self, other: Rational) -> Option<Rational> {
+    Rational::reduce(
+      self.numerator as i128 * other.denominator as i128,
+      self.denominator as i128 * other.numerator as i128,
+    )
+  }
+
+  /// Negates the fraction, returns None for a numerator of i64::MIN
+  pub fn checked_neg(self) -> Option<Rational> {
+    Rational::reduce(-(self.numerator as i128), self.denominator as i128)
+  }
+}
+
+impl fmt::Display for Rational {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if *self == INVALID {
+      write!(f, "NaN")
+    } else if self.denominator == 1 {
+      write!(f, "{}", self.numerator)
+    } else {
+      write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+  }
+}
+
+impl Add for Rational {
+  type Output = Rational;
+
+  fn add(self, other: Rational) -> Rational {
+    self.checked_add(other).unwrap_or(INVALID)
+  }
+}
+
+impl Sub for Rational {
+  type Output = Rational;
+
+  fn sub(self, other: Rational) -> Rational {
+    self.checked_sub(other).unwrap_or(INVALID)
+  }
+}
+
+impl Mul for Rational {
+  type Output = Rational;
+
+  fn mul(self, other: Rational) -> Rational {
+    self.checked_mul(other).unwrap_or(INVALID)
+  }
+}
+
+impl Div for Rational {
+  type Output = Rational;
+
+  fn div(self, other: Rational) -> Rational {
+    self.checked_div(other).unwrap_or(INVALID)
+  }
+}
+
+impl Neg for Rational {
+  type Output = Rational;
+
+  fn neg(self) -> Rational {
+    self.checked_neg().unwrap_or(INVALID)
+  }
+}
+
+impl Coefficient for Rational {
+  fn zero() -> Self {
+    Rational::new(0, 1)
+  }
+
+  fn one() -> Self {
+    Rational::new(1, 1)
+  }
+
+  fn is_zero(&self) -> bool {
+    self.numerator == 0 && self.denominator != 0
+  }
+
+  // Finds the closest fraction with a continued fraction expansion
+  fn from_float(f: Prec) -> Option<Self> {
+    if !f.is_finite() {
+      return None;
+    }
+
+    let target = f64::from(f);
+    let mut x = target;
+    let (mut h0, mut h1) = (0i64, 1i64);
+    let (mut k0, mut k1) = (1i64, 0i64);
+
+    for _ in 0..32 {
+      let a = x.floor();
+      if a.abs() >= i64::MAX as f64 {
+        break;
+      }
+      let h = (a as i64).checked_mul(h1)?.checked_add(h0)?;
+      let k = (a as i64).checked_mul(k1)?.checked_add(k0)?;
+      if k > 1_000_000 {
+        break;
+      }
+      if (h as f64 / k as f64 - target).abs() <= 1e-6 * target.abs().max(1.0) {
+        return Some(Rational::new(h, k));
+      }
+
+      h0 = h1;
+      h1 = h;
+      k0 = k1;
+      k1 = k;
+      x = 1.0 / (x - a);
+    }
+
+    None
+  }
+
+  fn to_float(&self) -> Prec {
+    self.numerator as Prec / self.denominator as Prec
+  }
+}
+
+// Variables with their power, sorted by variable name and without zero powers
+type Monomial = Vec<(String, u32)>;
+
+fn monomial_degree(m: &Monomial) -> u32 {
+  m.iter().map(|(_, p)| p).sum()
+}
+
+fn monomial_power(m: &Monomial, var: &str) -> u32 {
+  m.iter()
+    .find(|(v, _)| v == var)
+    .map(|(_, p)| *p)
+    .unwrap_or(0)
+}
+
+fn monomial_mul(a: &Monomial, b: &Monomial) -> Monomial {
+  let mut product = a.clone();
+  for (var, power) in b {
+    match product.iter_mut().find(|(v, _)| v == var) {
+      Some((_, p)) => *p += power,
+      None => product.push((var.clone(), *power)),
+    }
+  }
+  product.sort();
+  product
+}
+
+// Divides monomial a by b, returns None if b does not divide a
+fn monomial_div(a: &Monomial, b: &Monomial) -> Option<Monomial> {
+  let mut quotient = a.clone();
+  for (var, power) in b {
+    let (_, p) = quotient.iter_mut().find(|(v, _)| v == var)?;
+    if *p < *power {
+      return None;
+    }
+    *p -= power;
+  }
+  quotient.retain(|(_, p)| *p > 0);
+  Some(quotient)
+}
+
+// Lexicographic monomial order, variables are ordered alphabetically
+fn lex_cmp(a: &Monomial, b: &Monomial) -> Ordering {
+  let (mut a, mut b) = (a.iter(), b.iter());
+  loop {
+    return match (a.next(), b.next()) {
+      (None, None) => Ordering::Equal,
+      (Some(_), None) => Ordering::Greater,
+      (None, Some(_)) => Ordering::Less,
+      (Some((va, pa)), Some((vb, pb))) => match va.cmp(vb) {
+        // The first monomial contains a variable that comes first in the order
+        Ordering::Less => Ordering::Greater,
+        Ordering::Greater => Ordering::Less,
+        Ordering::Equal if pa == pb => continue,
+        Ordering::Equal => pa.cmp(pb),
+      },
+    };
+  }
+}
+
+/// A sparse multivariate polynomial with float or rational coefficients.
+///
+/// Polynomials can be created from any component tree which only consists of
+/// additions, subtractions, multiplications, divisions by numbers and integer powers.
+///
+/// # Examples
+/// ```
+/// use calculi::{Equation, Polynomial};
+///
+/// let eq = Equation::new("(x + 1) * (x - 1)");
+/// let p: Polynomial = Polynomial::from_component(&eq.expression).unwrap();
+///
+/// assert_eq!(p.to_string(), "x ^ 2 - 1");
+/// assert_eq!(p.evaluate(vec![("x", 3.0)]), Some(8.0));
+/// assert_eq!(p.roots(), Some(vec![-1.0, 1.0]));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial<C: Coefficient = Prec> {
+  terms: BTreeMap<Monomial, C>,
+}
+
+impl<C: Coefficient> Polynomial<C> {
+  pub fn zero() -> Polynomial<C> {
+    Polynomial {
+      terms: BTreeMap::new(),
+    }
+  }
+
+  pub fn constant(c: C) -> Polynomial<C> {
+    Self::monomial(c, &[])
+  }
+
+  pub fn variable(var: &str) -> Polynomial<C> {
+    Self::monomial(C::one(), &[(var, 1)])
+  }
+
+  /// Creates a single term polynomial, e.g. `monomial(3.0, &[("x", 2), ("y", 1)])` for `3 * x ^ 2 * y`
+  pub fn monomial(c: C, powers: &[(&str, u32)]) -> Polynomial<C> {
+    let mut monomial: Monomial = vec![];
+    for (var, power) in powers.iter().filter(|(_, p)| *p > 0) {
+      monomial = monomial_mul(&monomial, &vec![(var.to_string(), *power)]);
+    }
+
+    let mut terms = BTreeMap::new();
+    if !c.is_zero() {
+      terms.insert(monomial, c);
+    }
+    Polynomial { terms }
+  }
+
+  /// Attempts to convert a component tree to a polynomial
+  /// Returns None if the component is not polynomial, has a power above 1000 or contains numbers the coefficient can't represent
+  pub fn from_component(component: &Component) -> Option<Polynomial<C>> {
+    match component {
+      Component::Number(f) => Some(Self::constant(C::from_float(*f)?)),
+      Component::Variable(c) => Some(Self::variable(c)),
+      Component::Function { operator, values } if values.len() == 2 => {
+        let left = Self::from_component(&values[0])?;
+
+        match operator {
+          Add => Some(left + Self::from_component(&values[1])?),
+          Subtract => Some(left - Self::from_component(&values[1])?),
+          Multiply => Some(left * Self::from_component(&values[1])?),
+          Divide => match values[1] {
+            Component::Number(f) if f != 0.0 => Some(left.scale(C::one() / C::from_float(f)?)),
+            _ => None,
+          },
+          Exponent | Pow => match values[1] {
+            Component::Number(f) if f >= 0.0 && f.fract() == 0.0 && f <= MAX_POWER => {
+              Some(left.pow(f as u32))
+            }
+            _ => None,
+          },
+          _ => None,
+        }
+      }
+      _ => None,
+    }
+  }
+
+  /// Converts the polynomial to a component tree, terms with the highest degree come first
+  pub fn to_component(&self) -> Component {
+    let mut terms: Vec<_> = self.terms.iter().collect();
+    terms.sort_by(|(a, _), (b, _)| {
+      monomial_degree(b)
+        .cmp(&monomial_degree(a))
+        .then_with(|| lex_cmp(b, a))
+    });
+
+    let mut result: Option<Component> = None;
+    for (monomial, c) in terms {
+      // Small coefficients are kept as they are instead of being rounded to zero
+      let c = match c.to_float() {
+        c if c.abs() < 0.5 => c,
+        c => snap(c),
+      };
+
+      let mut power: Option<Component> = None;
+      for (var, p) in monomial {
+        let factor = if *p == 1 {
+          Component::Variable(var.to_string())
+        } else {
+          create_binary(
+            Exponent,
+            Component::Variable(var.to_string()),
+            Component::Number(*p as Prec),
+          )
+        };
+        power = Some(match power {
+          Some(power) => create_binary(Multiply, power, factor),
+          None => factor,
+        });
+      }
+
+      // The sign is moved into the operator when the term is added to a previous term
+      let magnitude = if result.is_some() { c.abs() } else { c };
+      let term = match power {
+        None => Component::Number(magnitude),
+        Some(power) if (magnitude - 1.0).abs() < Prec::EPSILON => power,
+        Some(power) => create_binary(Multiply, Component::Number(magnitude), power),
+      };
+
+      result = Some(match result {
+        None => term,
+        Some(prev) => create_binary(if c < 0.0 { Subtract } else { Add }, prev, term),
+      });
+    }
+
+    result.unwrap_or(Component::Number(0.0))
+  }
+
+  pub fn is_zero(&self) -> bool {
+    self.terms.is_empty()
+  }
+
+  /// All variables in the polynomial, sorted alphabetically
+  pub fn variables(&self) -> Vec<String> {
+    let mut vars: Vec<_> = self
+      .terms
+      .keys()
+      .flat_map(|m| m.iter().map(|(v, _)| v.clone()))
+      .collect();
+    vars.sort();
+    vars.dedup();
+    vars
+  }
+
+  /// The total degree, the highest sum of powers of any term
+  pub fn degree(&self) -> u32 {
+    self.terms.keys().map(monomial_degree).max().unwrap_or(0)
+  }
+
+  /// The highest power of the given variable
+  pub fn degree_in(&self, var: &str) -> u32 {
+    self
+      .terms
+      .keys()
+      .map(|m| monomial_power(m, var))
+      .max()
+      .unwrap_or(0)
+  }
+
+  /// Splits the polynomial into the coefficients of every power of var, starting at var ^ 0
+  pub fn coefficients_in(&self, var: &str) -> Vec<Polynomial<C>> {
+    let mut coefficients = vec![Self::zero(); self.degree_in(var) as usize + 1];
+    for (monomial, c) in &self.terms {
+      let power = monomial_power(monomial, var);
+      let rest = monomial.iter().filter(|(v, _)| v != var).cloned().collect();
+      coefficients[power as usize].terms.insert(rest, c.clone());
+    }
+    coefficients
+  }
+
+  // Leading term in lexicographic order
+  fn leading_term(&self) -> Option<(&Monomial, &C)> {
+    self.terms.iter().max_by(|(a, _), (b, _)| lex_cmp(a, b))
+  }
+
+  /// The coefficient of the leading term in lexicographic order, 0 for the zero polynomial
+  pub fn leading_coefficient(&self) -> C {
+    match self.leading_term() {
+      Some((_, c)) => c.clone(),
+      None => C::zero(),
+    }
+  }
+
+  fn leading_coefficient_in(&self, var: &str) -> Polynomial<C> {
+    self.coefficients_in(var).pop().unwrap_or_else(Self::zero)
+  }
+
+  fn insert(&mut self, monomial: Monomial, c: C) {
+    let sum = match self.terms.remove(&monomial) {
+      Some(prev) => prev + c,
+      None => c,
+    };
+    if !sum.is_zero() {
+      self.terms.insert(monomial, sum);
+    }
+  }
+
+  /// Multiplies every coefficient with c
+  pub fn scale(&self, c: C) -> Polynomial<C> {
+    let mut scaled = Self::zero();
+    for (monomial, coefficient) in &self.terms {
+      scaled.insert(monomial.clone(), coefficient.clone() * c.clone());
+    }
+    scaled
+  }
+
+  // Whether every coefficient is finite, operations on rationals which overflow give invalid coefficients
+  pub(crate) fn is_finite(&self) -> bool {
+    self.terms.values().all(|c| c.to_float().is_finite())
+  }
+
+  pub fn pow(&self, n: u32) -> Polynomial<C> {
+    (0..n).fold(Self::constant(C::one()), |acc, _| &acc * self)
+  }
+
+  /// Divides by another polynomial, returns the quotient and remainder
+  ///
+  /// For multivariate polynomials the leading terms are chosen with a lexicographic order.
+  /// Returns None when dividing by zero.
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::{Equation, Polynomial};
+  ///
+  /// let a: Polynomial = Polynomial::from_component(&Equation::new("x ^ 3 - 2 * x + 1").expression).unwrap();
+  /// let b = Polynomial::from_component(&Equation::new("x - 2").expression).unwrap();
+  ///
+  /// let (quotient, remainder) = a.div_rem(&b).unwrap();
+  ///
+  /// assert_eq!(quotient.to_string(), "x ^ 2 + 2 * x + 2");
+  /// assert_eq!(remainder.to_string(), "5");
+  /// ```
+  pub fn div_rem(&self, divisor: &Polynomial<C>) -> Option<(Polynomial<C>, Polynomial<C>)> {
+    let (lead_monomial, lead) = divisor.leading_term()?;
+    let mut quotient = Self::zero();
+    let mut remainder = Self::zero();
+    let mut rest = self.clone();
+
+    while let Some((monomial, c)) = rest.leading_term() {
+      let (monomial, c) = (monomial.clone(), c.clone());
+
+      match monomial_div(&monomial, lead_monomial) {
+        Some(factor) => {
+          let term = Polynomial::monomial_term(factor, c / lead.clone());
+          rest = &rest - &(&term * divisor);
+          quotient = quotient + term;
+        }
+        None => {
+          remainder.insert(monomial.clone(), c.clone());
+          rest.insert(monomial.clone(), -c);
+        }
+      }
+      // The leading term is always eliminated, even when rounding leaves a tiny value
+      rest.terms.remove(&monomial);
+    }
+
+    Some((quotient, remainder))
+  }
+
+  fn monomial_term(monomial: Monomial, c: C) -> Polynomial<C> {
+    let mut terms = BTreeMap::new();
+    if !c.is_zero() {
+      terms.insert(monomial, c);
+    }
+    Polynomial { terms }
+  }
+
+  // Removes the terms which are rounding noise, the remainders of the gcd and sturm sequence would never become zero otherwise
+  fn without_noise(&self) -> Polynomial<C> {
+    let mut terms = self.terms.clone();
+    terms.retain(|_, c| !c.is_negligible());
+    Polynomial { terms }
+  }
+
+  // Divides by the leading coefficient, so the leading term becomes 1
+  fn normalized(&self) -> Polynomial<C> {
+    if self.is_zero() {
+      self.clone()
+    } else {
+      self.scale(C::one() / self.leading_coefficient())
+    }
+  }
+
+  // Splits into the content (gcd of the coefficients in var) and primitive part
+  fn content_primitive(&self, var: &str) -> (Polynomial<C>, Polynomial<C>) {
+    let content = self
+      .coefficients_in(var)
+      .iter()
+      .filter(|c| !c.is_zero())
+      .fold(Self::zero(), |acc, c| acc.gcd(c))
+      .without_noise();
+
+    match self.div_rem(&content) {
+      Some((primitive, _)) => (content, primitive),
+      None => (Self::constant(C::one()), self.clone()),
+    }
+  }
+
+  // Remainder of lc(b) ^ n * a divided by b in var, which does not require division of coefficients
+  fn pseudo_remainder(&self, divisor: &Polynomial<C>, var: &str) -> Polynomial<C> {
+    let degree = divisor.degree_in(var);
+    let lead = divisor.leading_coefficient_in(var);
+    let mut rest = self.clone();
+
+    while !rest.is_zero() && rest.degree_in(var) >= degree {
+      let rest_degree = rest.degree_in(var);
+      let shift = Polynomial::monomial(C::one(), &[(var, rest_degree - degree)]);
+      rest = &(&rest * &lead) - &(&(&rest.leading_coefficient_in(var) * &shift) * divisor);
+
+      // The leading power is always eliminated, even when rounding leaves tiny values
+      rest
+        .terms
+        .retain(|m, _| monomial_power(m, var) < rest_degree);
+    }
+
+    rest
+  }
+
+  /// The greatest common divisor, normalized so its leading coefficient is 1
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::{Equation, Polynomial, Rational};
+  ///
+  /// let a: Polynomial<Rational> = Polynomial::from_component(&Equation::new("x ^ 2 * y - y").expression).unwrap();
+  /// let b = Polynomial::from_component(&Equation::new("x * y + y").expression).unwrap();
+  ///
+  /// assert_eq!(a.gcd(&b).to_string(), "x * y + y");
+  /// ```
+  pub fn gcd(&self, other: &Polynomial<C>) -> Polynomial<C> {
+    if self.is_zero() {
+      return other.normalized();
+    } else if other.is_zero() {
+      return self.normalized();
+    }
+
+    let mut vars = self.variables();
+    vars.extend(other.variables());
+    vars.sort();
+
+    // The gcd of two constants is 1, since the coefficients are a field
+    let var = match vars.first() {
+      Some(var) => var.clone(),
+      None => return Self::constant(C::one()),
+    };
+
+    // Coefficients of var contain one variable less, so the recursion ends
+    let (content_a, mut a) = self.content_primitive(&var);
+    let (content_b, mut b) = other.content_primitive(&var);
+    let content = content_a.gcd(&content_b);
+
+    if a.degree_in(&var) < b.degree_in(&var) {
+      std::mem::swap(&mut a, &mut b);
+    }
+
+    while !b.is_zero() {
+      let remainder = a.pseudo_remainder(&b, &var).without_noise();
+      a = b;
+      b = if remainder.is_zero() {
+        remainder
+      } else {
+        remainder.content_primitive(&var).1.normalized()
+      };
+    }
+
+    (&a * &content).normalized()
+  }
+
+  /// The partial derivative with respect to var
+  pub fn derivative(&self, var: &str) -> Polynomial<C> {
+    let mut derivative = Self::zero();
+    for (monomial, c) in &self.terms {
+      let power = monomial_power(monomial, var);
+      if power == 0 {
+        continue;
+      }
+
+      let mut factor = C::zero();
+      for _ in 0..power {
+        factor = factor + C::one();
+      }
+
+      let monomial = monomial
+        .iter()
+        .map(|(v, p)| (v.clone(), if v == var { p - 1 } else { *p }))
+        .filter(|(_, p)| *p > 0)
+        .collect();
+      derivative.insert(monomial, c.clone() * factor);
+    }
+    derivative
+  }
+
+  /// Evaluates the polynomial with horner's method
+  /// Returns None if a variable of the polynomial is not given
+  pub fn evaluate<'a>(&self, vars: impl IntoIterator<Item = (&'a str, Prec)>) -> Option<Prec> {
+    let vars: HashMap<_, _> = vars.into_iter().collect();
+    self.horner(&vars)
+  }
+
+  // Horner's method on the first variable, with the coefficients evaluated recursively
+  fn horner(&self, vars: &HashMap<&str, Prec>) -> Option<Prec> {
+    match self.variables().first() {
+      None => Some(
+        self
+          .terms
+          .values()
+          .next()
+          .map(|c| c.to_float())
+          .unwrap_or(0.0),
+      ),
+      Some(var) => {
+        let x = *vars.get(var.as_str())?;
+        self
+          .coefficients_in(var)
+          .iter()
+          .rev()
+          .try_fold(0.0, |acc, c| Some(acc * x + c.horner(vars)?))
+      }
+    }
+  }
+
+  // Sturm sequence of a univariate polynomial, the number of sign changes at a point
+  // is used to count the distinct real roots in an interval
+  fn sturm_sequence(&self, var: &str) -> Vec<Polynomial<C>> {
+    let mut sequence = vec![self.clone(), self.derivative(var)];
+    loop {
+      let len = sequence.len();
+      if sequence[len - 1].is_zero() {
+        sequence.pop();
+        return sequence;
+      }
+
+      let (_, remainder) = sequence[len - 2].div_rem(&sequence[len - 1]).unwrap();
+      sequence.push(-remainder.without_noise());
+    }
+  }
+
+  /// Finds intervals `(a, b]` which each contain exactly one distinct real root
+  /// Returns None if the polynomial contains more than one variable
+  ///
+  /// # Examples
+  /// ```
+  /// use calculi::{Equation, Polynomial};
+  ///
+  /// let p: Polynomial = Polynomial::from_component(&Equation::new("x ^ 3 - x").expression).unwrap();
+  ///
+  /// assert_eq!(p.isolate_roots().unwrap().len(), 3);
+  /// ```
+  pub fn isolate_roots(&self) -> Option<Vec<(Prec, Prec)>> {
+    let vars = self.variables();
+    let var = match vars.len() {
+      0 => return Some(vec![]),
+      1 => vars[0].as_str(),
+      _ => return None,
+    };
+
+    let sturm = self.sturm_sequence(var);
+    let count = |x: Prec| sign_changes(&sturm, var, x);
+
+    // Fujiwara's bound, all roots lie within (-bound, bound)
+    // The coefficients are divided by the leading one in double precision, so their ratios don't overflow
+    let coefficients = self.coefficients_in(var);
+    let n = coefficients.len() - 1;
+    let float = |c: &Polynomial<C>| f64::from(c.horner(&HashMap::new()).unwrap_or(0.0));
+    let lead = float(&coefficients[n]);
+    let bound = 2.0
+      * coefficients[..n]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+          let ratio = (float(c) / lead).abs() / if i == 0 { 2.0 } else { 1.0 };
+          ratio.powf(1.0 / (n - i) as f64)
+        })
+        .fold(0.0, f64::max);
+    let bound = (bound as Prec).min(Prec::MAX) + 1.0;
+
+    let mut intervals = vec![];
+    let mut pending = vec![(-bound, bound, 0)];
+    while let Some((a, b, depth)) = pending.pop() {
+      let roots = count(a) as i64 - count(b) as i64;
+      if roots == 1 || (roots > 1 && depth > 64) {
+        intervals.push((a, b));
+      } else if roots > 1 {
+        let middle = (a + b) / 2.0;
+        pending.push((middle, b, depth + 1));
+        pending.push((a, middle, depth + 1));
+      }
+    }
+
+    intervals.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    Some(intervals)
+  }
+
+  /// Finds all distinct real roots, sorted from low to high
+  /// Returns None if the polynomial contains more than one variable
+  pub fn roots(&self) -> Option<Vec<Prec>> {
+    let var = self.variables().pop();
+    let sturm = var.as_ref().map(|var| self.sturm_sequence(var));
+    let count = |x: Prec| match (&var, &sturm) {
+      (Some(var), Some(sturm)) => sign_changes(sturm, var, x),
+      _ => 0,
+    };
+
+    // Bisect every interval, keeping the half that contains the root
+    let intervals = self.isolate_roots()?;
+    Some(
+      intervals
+        .into_iter()
+        .map(|(mut a, mut b)| {
+          for _ in 0..64 {
+            let middle = (a + b) / 2.0;
+            if middle <= a || middle >= b {
+              break;
+            }
+            if count(a) > count(middle) {
+              b = middle;
+            } else {
+              a = middle;
+            }
+          }
+          snap(b)
+        })
+        .collect(),
+    )
+  }
+}
+
+// Number of sign changes of a sturm sequence at x, ignoring zeros
+fn sign_changes<C: Coefficient>(sturm: &[Polynomial<C>], var: &str, x: Prec) -> usize {
+  let signs: Vec<_> = sturm
+    .iter()
+    .map(|p| p.evaluate(vec![(var, x)]).unwrap_or(0.0))
+    .filter(|f| *f != 0.0)
+    .collect();
+  signs
+    .windows(2)
+    .filter(|w| w[0].signum() != w[1].signum())
+    .count()
+}
+
+impl<C: Coefficient> fmt::Display for Polynomial<C> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.to_component().fmt(f)
+  }
+}
+
+impl<'a, C: Coefficient> Add<&'a Polynomial<C>> for &'a Polynomial<C> {
+  type Output = Polynomial<C>;
+
+  fn add(self, other: &Polynomial<C>) -> Polynomial<C> {
+    let mut sum = self.clone();
+    for (monomial, c) in &other.terms {
+      sum.insert(monomial.clone(), c.clone());
+    }
+    sum
+  }
+}
+
+impl<'a, C: Coefficient> Sub<&'a Polynomial<C>> for &'a Polynomial<C> {
+  type Output = Polynomial<C>;
+
+  fn sub(self, other: &Polynomial<C>) -> Polynomial<C> {
+    self + &-other.clone()
+  }
+}
+
+impl<'a, C: Coefficient> Mul<&'a Polynomial<C>> for &'a Polynomial<C> {
+  type Output = Polynomial<C>;
+
+  fn mul(self, other: &Polynomial<C>) -> Polynomial<C> {
+    let mut product = Polynomial::zero();
+    for (ma, ca) in &self.terms {
+      for (mb, cb) in &other.terms {
+        product.insert(monomial_mul(ma, mb), ca.clone() * cb.clone());
+      }
+    }
+    product
+  }
+}
+
+impl<C: Coefficient> Add for Polynomial<C> {
+  type Output = Polynomial<C>;
+
+  fn add(self, other: Polynomial<C>) -> Polynomial<C> {
+    &self + &other
+  }
+}
+
+impl<C: Coefficient> Sub for Polynomial<C> {
+  type Output = Polynomial<C>;
+
+  fn sub(self, other: Polynomial<C>) -> Polynomial<C> {
+    &self - &other
+  }
+}
+
+impl<C: Coefficient> Mul for Polynomial<C> {
+  type Output = Polynomial<C>;
+
+  fn mul(self, other: Polynomial<C>) -> Polynomial<C> {
+    &self * &other
+  }
+}
+
+impl<C: Coefficient> Neg for Polynomial<C> {
+  type Output = Polynomial<C>;
+
+  fn neg(self) -> Polynomial<C> {
+    self.scale(-C::one())
+  }
+}
+
+// Dense univariate helpers, the coefficient of x ^ i is stored at index i
+
+// Removes the (near) zero coefficients of the highest powers
+fn trim(mut p: Vec<Prec>) -> Vec<Prec> {
+  while let Some(c) = p.last() {
+    if c.abs() > EPSILON {
+      break;
+    }
+    p.pop();
+  }
+  p
+}
+
+// Scales polynomial so its highest coefficient is 1
+fn monic(p: &[Prec]) -> Vec<Prec> {
+  match p.last() {
+    Some(lead) => p.iter().map(|c| c / lead).collect(),
+    None => vec![],
+  }
+}
+
+// Rounds values that are very close to an integer, to hide floating point noise
+pub(crate) fn snap(f: Prec) -> Prec {
+  if (f - f.round()).abs() < EPSILON {
+    f.round()
+  } else {
+    f
+  }
+}
+
+// Finds all (complex) roots of polynomial with the Durand-Kerner method
+// Roots are returned as (real, imaginary) pairs, they are computed in double precision so repeated and nearby roots stay apart
+fn roots(p: &[Prec]) -> Vec<(f64, f64)> {
+  let p: Vec<f64> = monic(&trim(p.to_vec())).iter().map(|c| *c as f64).collect();
+  let n = p.len().saturating_sub(1);
+  if n == 0 {
+    return vec![];
+  }
+
+  let eval = |x: (f64, f64)| {
+    p.iter().rev().fold((0.0, 0.0), |acc, c| {
+      (c + acc.0 * x.0 - acc.1 * x.1, acc.0 * x.1 + acc.1 * x.0)
+    })
+  };
+
+  // Initial guesses are powers of a complex number which is not a root of unity
+  let mut z: Vec<(f64, f64)> = Vec::with_capacity(n);
+  let mut guess = (0.4, 0.9);
+  for _ in 0..n {
+    z.push(guess);
+    guess = (guess.0 * 0.4 - guess.1 * 0.9, guess.0 * 0.9 + guess.1 * 0.4);
+  }
+
+  for _ in 0..1000 {
+    let mut change: f64 = 0.0;
+    for i in 0..n {
+      let mut denominator = (1.0, 0.0);
+      for (j, other) in z.iter().enumerate() {
+        if i != j {
+          let d = (z[i].0 - other.0, z[i].1 - other.1);
+          denominator = (
+            denominator.0 * d.0 - denominator.1 * d.1,
+            denominator.0 * d.1 + denominator.1 * d.0,
+          );
+        }
+      }
+
+      let value = eval(z[i]);
+      let norm = denominator.0 * denominator.0 + denominator.1 * denominator.1;
+      if norm == 0.0 {
+        continue;
+      }
+      let step = (
+        (value.0 * denominator.0 + value.1 * denominator.1) / norm,
+        (value.1 * denominator.0 - value.0 * denominator.1) / norm,
+      );
+      z[i] = (z[i].0 - step.0, z[i].1 - step.1);
+      change = change.max(step.0.abs() + step.1.abs());
+    }
+
+    if change < 1e-15 {
+      break;
+    }
+  }
+
+  z
+}
+
+// Factors polynomial into its real roots and complex conjugate root pairs
+// Returns (real, imaginary, multiplicity), only one root of every conjugate pair is returned
+// Roots closer to each other than a fraction of their size are merged and averaged, which stabilises repeated roots
+pub(crate) fn factor_roots(p: &[Prec]) -> Vec<(Prec, Prec, usize)> {
+  let tolerance = 1e-3;
+  let mut clusters: Vec<((f64, f64), usize)> = vec![];
+
+  for root in roots(p) {
+    // Only keep the root with a positive imaginary part of conjugate pairs
+    let root = (root.0, root.1.abs());
+    match clusters.iter_mut().find(|(c, m)| {
+      let mean = (c.0 / *m as f64, c.1 / *m as f64);
+      let size = 1.0 + mean.0.abs() + mean.1.abs();
+      (mean.0 - root.0).abs() + (mean.1 - root.1).abs() < tolerance * size
+    }) {
+      Some((sum, m)) => {
+        *sum = (sum.0 + root.0, sum.1 + root.1);
+        *m += 1;
+      }
+      None => clusters.push((root, 1)),
+    }
+  }
+
+  clusters
+    .into_iter()
+    .map(|(sum, m)| {
+      let (re, im) = (sum.0 / m as f64, sum.1 / m as f64);
+      let (re, im) = (snap(re as Prec), snap(im as Prec));
+      if (im as f64).abs() < tolerance * (1.0 + (re as f64).abs()) {
+        (re, 0.0, m)
+      } else {
+        // Both roots of the conjugate pair were counted in the cluster
+        (re, im, m / 2)
+      }
+    })
+    .filter(|(_, _, m)| *m > 0)
+    .collect()
+}
@@ -0,0 +1,371 @@
+use std::fmt;
+
+use super::component::Component;
+use super::latex::{parenthesized, GREEK};
+use super::mathml::GREEK_SYMBOLS;
+use super::operators::Operator::*;
+
+/// The characters the pretty printer draws with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Charset {
+  /// Box drawing characters, greek letters and subscript digits
+  Unicode,
+  /// Only ascii characters, for terminals without unicode support
+  Ascii,
+}
+
+/// A component laid out over multiple lines, with fractions, exponents and radicals drawn in two dimensions
+/// Created with [Component::pretty](../component/enum.Component.html#method.pretty)
+pub struct Pretty<'a> {
+  component: &'a Component,
+  charset: Charset,
+}
+
+impl<'a> Pretty<'a> {
+  pub(crate) fn new(component: &'a Component, charset: Charset) -> Pretty<'a> {
+    Pretty { component, charset }
+  }
+}
+
+impl fmt::Display for Pretty<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let lines = layout(self.component, self.charset).lines;
+    let lines: Vec<_> = lines.iter().map(|line| line.trim_end()).collect();
+    f.write_str(&lines.join("\n"))
+  }
+}
+
+// A rectangle of text, the baseline is the line which lines up with the text around it
+#[derive(Debug, Clone)]
+struct Block {
+  lines: Vec<String>,
+  baseline: usize,
+}
+
+impl Block {
+  fn text(text: &str) -> Block {
+    Block {
+      lines: vec![text.to_string()],
+      baseline: 0,
+    }
+  }
+
+  fn width(&self) -> usize {
+    self
+      .lines
+      .iter()
+      .map(|line| line.chars().count())
+      .max()
+      .unwrap_or(0)
+  }
+
+  fn height(&self) -> usize {
+    self.lines.len()
+  }
+
+  // Pads every line with spaces to the same width, centered if there is room
+  fn centered(&self, width: usize) -> Vec<String> {
+    let own = self.width();
+    let left = (width - own) / 2;
+    self
+      .lines
+      .iter()
+      .map(|line| {
+        let right = width - left - line.chars().count();
+        format!("{}{}{}", " ".repeat(left), line, " ".repeat(right))
+      })
+      .collect()
+  }
+
+  // Places blocks next to each other, lining up their baselines
+  fn row(blocks: &[Block]) -> Block {
+    let above = blocks.iter().map(|b| b.baseline).max().unwrap_or(0);
+    let below = blocks
+      .iter()
+      .map(|b| b.height() - b.baseline - 1)
+      .max()
+      .unwrap_or(0);
+    let mut lines = vec![String::new(); above + below + 1];
+    for block in blocks {
+      let width = block.width();
+      let top = above - block.baseline;
+      for (i, line) in lines.iter_mut().enumerate() {
+        let text = match i.checked_sub(top).and_then(|i| block.lines.get(i)) {
+          Some(text) => text.clone(),
+          None => String::new(),
+        };
+        let padding = width - text.chars().count();
+        line.push_str(&text);
+        line.push_str(&" ".repeat(padding));
+      }
+    }
+    Block {
+      lines,
+      baseline: above,
+    }
+  }
+
+  // Stacks blocks on top of each other, centered, with the baseline in the given block
+  fn column(blocks: &[Block], baseline: usize) -> Block {
+    let width = blocks.iter().map(Block::width).max().unwrap_or(0);
+    let offset: usize = blocks[..baseline].iter().map(Block::height).sum();
+    Block {
+      lines: blocks.iter().flat_map(|b| b.centered(width)).collect(),
+      baseline: offset + blocks[baseline].baseline,
+    }
+  }
+}
+
+// The characters for one charset
+struct Symbols {
+  bar: char,
+  times: &'static str,
+  parentheses: [[char; 4]; 2],
+  vertical: char,
+  radical: [char; 3],
+  sum: &'static [&'static str],
+  product: &'static [&'static str],
+}
+
+const UNICODE: Symbols = Symbols {
+  bar: '─',
+  times: " ⋅ ",
+  parentheses: [['(', '⎛', '⎜', '⎝'], [')', '⎞', '⎟', '⎠']],
+  vertical: '│',
+  radical: ['╲', '╱', '_'],
+  sum: &["___", "╲  ", "╱  ", "‾‾‾"],
+  product: &["┬─┬", "│ │", "│ │"],
+};
+
+const ASCII: Symbols = Symbols {
+  bar: '-',
+  times: " * ",
+  parentheses: [['(', '/', '|', '\\'], [')', '\\', '|', '/']],
+  vertical: '|',
+  radical: ['\\', '/', '_'],
+  sum: &["___", "\\  ", "/  ", "---"],
+  product: &["___", "| |", "| |"],
+};
+
+struct Printer {
+  charset: Charset,
+  symbols: Symbols,
+}
+
+impl Printer {
+  // Surrounds a block with brackets which are as high as the block
+  // The brackets are given as the character for a single line and the top, middle and bottom characters
+  fn bracket(&self, block: Block, [open, close]: [[char; 4]; 2]) -> Block {
+    let height = block.height();
+    let side = |chars: [char; 4]| Block {
+      lines: (0..height)
+        .map(|i| match i {
+          _ if height == 1 => chars[0].to_string(),
+          0 => chars[1].to_string(),
+          _ if i == height - 1 => chars[3].to_string(),
+          _ => chars[2].to_string(),
+        })
+        .collect(),
+      baseline: block.baseline,
+    };
+    Block::row(&[side(open), block.clone(), side(close)])
+  }
+
+  fn parentheses(&self, block: Block) -> Block {
+    self.bracket(block, self.symbols.parentheses)
+  }
+
+  fn variable(&self, name: &str) -> String {
+    if self.charset == Charset::Ascii {
+      return name.to_string();
+    }
+    let (name, subscript) = match name.find('_') {
+      Some(i) => (&name[..i], Some(&name[i + 1..])),
+      None => (name, None),
+    };
+    let mut text = match GREEK.iter().position(|letter| *letter == name) {
+      Some(i) => GREEK_SYMBOLS[i].to_string(),
+      None => name.to_string(),
+    };
+    match subscript {
+      // Digits have subscript characters
+      Some(digits) if digits.chars().all(|c| c.is_ascii_digit()) => {
+        text.extend(
+          digits
+            .chars()
+            .map(|c| std::char::from_u32('₀' as u32 + c.to_digit(10).unwrap()).unwrap()),
+        );
+      }
+      Some(subscript) => text = format!("{}_{}", text, subscript),
+      None => (),
+    }
+    text
+  }
+
+  fn fraction(&self, numerator: Block, denominator: Block) -> Block {
+    let width = numerator.width().max(denominator.width()) + 2;
+    let bar = Block::text(&self.symbols.bar.to_string().repeat(width));
+    Block::column(&[numerator, bar, denominator], 1)
+  }
+
+  // Raises the exponent to the top right of the base
+  fn power(&self, base: Block, exponent: Block) -> Block {
+    let (base_width, exponent_width) = (base.width(), exponent.width());
+    let lines = exponent
+      .centered(exponent_width)
+      .into_iter()
+      .map(|line| format!("{}{}", " ".repeat(base_width), line))
+      .chain(
+        base
+          .centered(base_width)
+          .into_iter()
+          .map(|line| format!("{}{}", line, " ".repeat(exponent_width))),
+      )
+      .collect();
+    Block {
+      lines,
+      baseline: exponent.height() + base.baseline,
+    }
+  }
+
+  // Draws a radical sign as high as the radicand, with a bar above it
+  fn radical(&self, radicand: Block, index: Option<Block>) -> Block {
+    let [back, slash, bar] = self.symbols.radical;
+    let (height, width) = (radicand.height(), radicand.width());
+    let mut lines = vec![format!(
+      "{}{}",
+      " ".repeat(height + 1),
+      bar.to_string().repeat(width + 1)
+    )];
+    for (i, line) in radicand.centered(width).iter().enumerate() {
+      let before = if i == height - 1 {
+        back.to_string()
+      } else {
+        " ".repeat(height - i)
+      };
+      lines.push(format!("{}{}{} {}", before, slash, " ".repeat(i), line));
+    }
+
+    match index {
+      // The index sits left of the bar
+      Some(index) => {
+        let above = index.height() - 1;
+        let index = Block {
+          baseline: above,
+          lines: index.lines,
+        };
+        let row = Block::row(&[index, Block { lines, baseline: 0 }]);
+        Block {
+          baseline: above + radicand.baseline + 1,
+          lines: row.lines,
+        }
+      }
+      None => Block {
+        lines,
+        baseline: radicand.baseline + 1,
+      },
+    }
+  }
+
+  // Sum or product sign with the bounds above and below
+  fn big_operator(&self, sign: &[&str], k: Block, from: Block, to: Block, body: Block) -> Block {
+    let sign = Block {
+      lines: sign.iter().map(|line| line.to_string()).collect(),
+      baseline: 2,
+    };
+    let lower = Block::row(&[k, Block::text(" = "), from]);
+    let operator = Block::column(&[to, sign, lower], 1);
+    Block::row(&[operator, Block::text(" "), body])
+  }
+
+  fn function(&self, name: Block, arguments: &[Block]) -> Block {
+    let mut blocks = vec![];
+    for (i, argument) in arguments.iter().enumerate() {
+      if i > 0 {
+        blocks.push(Block::text(", "));
+      }
+      blocks.push(argument.clone());
+    }
+    Block::row(&[name, self.parentheses(Block::row(&blocks))])
+  }
+
+  fn layout(&self, component: &Component) -> Block {
+    let (operator, values) = match component {
+      Component::Variable(c) => return Block::text(&self.variable(c)),
+      Component::Number(f) => return Block::text(&f.to_string()),
+      Component::Function { operator, values } => (operator, values),
+      Component::End => return Block::text(""),
+    };
+
+    let operand = |i: usize| {
+      let block = self.layout(&values[i]);
+      if parenthesized(operator, i, &values[i]) {
+        self.parentheses(block)
+      } else {
+        block
+      }
+    };
+    let infix = |symbol: &str| Block::row(&[operand(0), Block::text(symbol), operand(1)]);
+    let unicode = self.charset == Charset::Unicode;
+
+    match (operator, values.as_slice()) {
+      (Add, [_, _]) => infix(" + "),
+      (Subtract, [_, _]) => infix(" - "),
+      (Multiply, [_, _]) => infix(self.symbols.times),
+      (Modulo, [_, _]) => infix(" mod "),
+      (Divide, [a, b]) => self.fraction(self.layout(a), self.layout(b)),
+      (Exponent, [_, b]) | (Pow, [_, b]) => self.power(operand(0), self.layout(b)),
+      (Exp, [a]) => self.power(Block::text("e"), self.layout(a)),
+      (Sqrt, [a]) => self.radical(self.layout(a), None),
+      (Root, [a, b]) => self.radical(self.layout(a), Some(self.layout(b))),
+      (Log, [a, b]) => {
+        // The base is lowered below the baseline
+        let base = self.layout(b);
+        let lowered = Block {
+          lines: std::iter::once(String::new()).chain(base.lines).collect(),
+          baseline: 0,
+        };
+        self.function(
+          Block::row(&[Block::text("log"), lowered]),
+          &[self.layout(a)],
+        )
+      }
+      (Abs, [a]) => {
+        let v = self.symbols.vertical;
+        self.bracket(self.layout(a), [[v; 4], [v; 4]])
+      }
+      (Floor, [a]) if unicode => {
+        self.bracket(self.layout(a), [['⌊', '⎢', '⎢', '⌊'], ['⌋', '⎥', '⎥', '⌋']])
+      }
+      (Ceil, [a]) if unicode => {
+        self.bracket(self.layout(a), [['⌈', '⌈', '⎢', '⎢'], ['⌉', '⌉', '⎥', '⎥']])
+      }
+      (Sum, [_, k, from, to]) | (Product, [_, k, from, to]) => self.big_operator(
+        if operator.compare(&Sum) {
+          self.symbols.sum
+        } else {
+          self.symbols.product
+        },
+        self.layout(k),
+        self.layout(from),
+        self.layout(to),
+        operand(0),
+      ),
+      _ => {
+        let arguments: Vec<_> = values.iter().map(|value| self.layout(value)).collect();
+        self.function(Block::text(&operator.to_string()), &arguments)
+      }
+    }
+  }
+}
+
+fn layout(component: &Component, charset: Charset) -> Block {
+  let printer = Printer {
+    charset,
+    symbols: match charset {
+      Charset::Unicode => UNICODE,
+      Charset::Ascii => ASCII,
+    },
+  };
+  printer.layout(component)
+}
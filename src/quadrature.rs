@@ -0,0 +1,222 @@
+use super::component::Prec;
+
+/// The available methods for numerical integration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quadrature {
+  /// Adaptive 15 point Gauss-Kronrod, accurate for smooth integrands with few evaluations
+  GaussKronrod,
+  /// Adaptive Simpson's rule, simple but needs more evaluations
+  Simpson,
+}
+
+/// The outcome of a numerical integration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Integral {
+  /// The approximated value of the integral
+  pub value: Prec,
+  /// An estimate of the absolute error of the value
+  pub error: Prec,
+}
+
+// Requested accuracy, limited by the precision of the integrand
+const ABSOLUTE_TOLERANCE: f64 = 1e-6;
+const RELATIVE_TOLERANCE: f64 = 1e-5;
+
+// Maximum amount of times an interval is split
+const MAX_SUBDIVISIONS: usize = 200;
+const MAX_SIMPSON_DEPTH: usize = 30;
+
+// Kronrod nodes, every odd node is a node of the 7 point gauss rule
+const KRONROD_NODES: [f64; 8] = [
+  0.991_455_371_120_812_6,
+  0.949_107_912_342_758_5,
+  0.864_864_423_359_769_1,
+  0.741_531_185_599_394_4,
+  0.586_087_235_467_691_1,
+  0.405_845_151_377_397_2,
+  0.207_784_955_007_898_5,
+  0.0,
+];
+
+const KRONROD_WEIGHTS: [f64; 8] = [
+  0.022_935_322_010_529_225,
+  0.063_092_092_629_978_55,
+  0.104_790_010_322_250_18,
+  0.140_653_259_715_525_92,
+  0.169_004_726_639_267_9,
+  0.190_350_578_064_785_4,
+  0.204_432_940_075_298_9,
+  0.209_482_141_084_727_83,
+];
+
+const GAUSS_WEIGHTS: [f64; 4] = [
+  0.129_484_966_168_869_7,
+  0.279_705_391_489_276_7,
+  0.381_830_050_505_118_9,
+  0.417_959_183_673_469_4,
+];
+
+// Applies the 15 point Kronrod rule to an interval, the difference with
+// the embedded 7 point Gauss rule is the error estimate
+fn gauss_kronrod(f: &dyn Fn(f64) -> f64, a: f64, b: f64) -> (f64, f64) {
+  let center = (a + b) / 2.0;
+  let half = (b - a) / 2.0;
+  let mut kronrod = 0.0;
+  let mut gauss = 0.0;
+
+  for (i, node) in KRONROD_NODES.iter().enumerate() {
+    let value = if *node == 0.0 {
+      f(center)
+    } else {
+      f(center - half * node) + f(center + half * node)
+    };
+    kronrod += KRONROD_WEIGHTS[i] * value;
+    if i % 2 == 1 {
+      gauss += GAUSS_WEIGHTS[i / 2] * value;
+    }
+  }
+
+  (kronrod * half, ((kronrod - gauss) * half).abs())
+}
+
+fn adaptive_gauss_kronrod(f: &dyn Fn(f64) -> f64, a: f64, b: f64) -> (f64, f64) {
+  let (value, error) = gauss_kronrod(f, a, b);
+  let mut intervals = vec![(a, b, value, error)];
+
+  for _ in 0..MAX_SUBDIVISIONS {
+    let value: f64 = intervals.iter().map(|i| i.2).sum();
+    let error: f64 = intervals.iter().map(|i| i.3).sum();
+    if error <= ABSOLUTE_TOLERANCE.max(RELATIVE_TOLERANCE * value.abs()) || !error.is_finite() {
+      break;
+    }
+
+    // Split the interval with the largest error
+    let (worst, _) = intervals
+      .iter()
+      .enumerate()
+      .max_by(|(_, x), (_, y)| x.3.partial_cmp(&y.3).unwrap())
+      .unwrap();
+    let (a, b, _, _) = intervals.swap_remove(worst);
+    let middle = (a + b) / 2.0;
+    let (left, left_error) = gauss_kronrod(f, a, middle);
+    let (right, right_error) = gauss_kronrod(f, middle, b);
+    intervals.push((a, middle, left, left_error));
+    intervals.push((middle, b, right, right_error));
+  }
+
+  (
+    intervals.iter().map(|i| i.2).sum(),
+    intervals.iter().map(|i| i.3).sum(),
+  )
+}
+
+// Recursively halves the interval until simpson's rule agrees with the halves
+#[allow(clippy::too_many_arguments)]
+fn adaptive_simpson(
+  f: &dyn Fn(f64) -> f64,
+  a: f64,
+  b: f64,
+  fa: f64,
+  fm: f64,
+  fb: f64,
+  whole: f64,
+  tolerance: f64,
+  depth: usize,
+) -> (f64, f64) {
+  let middle = (a + b) / 2.0;
+  let (left_middle, right_middle) = ((a + middle) / 2.0, (middle + b) / 2.0);
+  let (flm, frm) = (f(left_middle), f(right_middle));
+  let left = (middle - a) / 6.0 * (fa + 4.0 * flm + fm);
+  let right = (b - middle) / 6.0 * (fm + 4.0 * frm + fb);
+  let difference = left + right - whole;
+
+  // Values which are not finite never converge, so they are returned right away
+  if !difference.is_finite() {
+    (difference, difference)
+  } else if depth >= MAX_SIMPSON_DEPTH || difference.abs() <= 15.0 * tolerance {
+    // Richardson extrapolation
+    (left + right + difference / 15.0, difference.abs() / 15.0)
+  } else {
+    let (l, le) = adaptive_simpson(f, a, middle, fa, flm, fm, left, tolerance / 2.0, depth + 1);
+    let (r, re) = adaptive_simpson(f, middle, b, fm, frm, fb, right, tolerance / 2.0, depth + 1);
+    (l + r, le + re)
+  }
+}
+
+fn simpson(f: &dyn Fn(f64) -> f64, a: f64, b: f64) -> (f64, f64) {
+  let (fa, fm, fb) = (f(a), f((a + b) / 2.0), f(b));
+  let whole = (b - a) / 6.0 * (fa + 4.0 * fm + fb);
+  adaptive_simpson(f, a, b, fa, fm, fb, whole, ABSOLUTE_TOLERANCE, 0)
+}
+
+/// Integrates f from a to b, bounds may be infinite
+/// Returns None if the integral could not be approximated
+pub fn integrate(
+  f: &dyn Fn(Prec) -> Prec,
+  a: Prec,
+  b: Prec,
+  method: Quadrature,
+) -> Option<Integral> {
+  if a == b {
+    return Some(Integral {
+      value: 0.0,
+      error: 0.0,
+    });
+  } else if a > b {
+    return integrate(f, b, a, method).map(|i| Integral {
+      value: -i.value,
+      error: i.error,
+    });
+  }
+
+  let (a, b) = (f64::from(a), f64::from(b));
+  let eval = |x: f64| f64::from(f(x as Prec));
+
+  // Infinite bounds are mapped to a finite interval, points which map to infinity
+  // are only reached by simpson's rule and contribute nothing
+  let transformed: Box<dyn Fn(f64) -> f64> = match (a.is_finite(), b.is_finite()) {
+    (true, true) => Box::new(eval),
+    // x = a + t / (1 - t), for t in [0, 1)
+    (true, false) => Box::new(move |t: f64| {
+      if t >= 1.0 {
+        return 0.0;
+      }
+      eval(a + t / (1.0 - t)) / ((1.0 - t) * (1.0 - t))
+    }),
+    // x = b - (1 - t) / t, for t in (0, 1]
+    (false, true) => Box::new(move |t: f64| {
+      if t <= 0.0 {
+        return 0.0;
+      }
+      eval(b - (1.0 - t) / t) / (t * t)
+    }),
+    // x = t / (1 - t ^ 2), for t in (-1, 1)
+    (false, false) => Box::new(move |t: f64| {
+      let s = 1.0 - t * t;
+      if s <= 0.0 {
+        return 0.0;
+      }
+      eval(t / s) * (1.0 + t * t) / (s * s)
+    }),
+  };
+  let (a, b) = match (a.is_finite(), b.is_finite()) {
+    (true, true) => (a, b),
+    (true, false) => (0.0, 1.0),
+    (false, true) => (0.0, 1.0),
+    (false, false) => (-1.0, 1.0),
+  };
+
+  let (value, error) = match method {
+    Quadrature::GaussKronrod => adaptive_gauss_kronrod(&*transformed, a, b),
+    Quadrature::Simpson => simpson(&*transformed, a, b),
+  };
+
+  if value.is_finite() && error.is_finite() {
+    Some(Integral {
+      value: value as Prec,
+      error: error as Prec,
+    })
+  } else {
+    None
+  }
+}
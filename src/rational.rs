@@ -0,0 +1,301 @@
+use super::component::{Component, Prec};
+use super::operators::{Operator, Operator::*};
+use super::polynomial;
+use super::polynomial::Polynomial;
+use super::utils::*;
+
+fn is_one(component: &Component) -> bool {
+  match component {
+    Component::Number(f) => (*f - 1.0).abs() < Prec::EPSILON,
+    _ => false,
+  }
+}
+
+// Multiplies two components, leaving out factors of 1
+fn product(left: Component, right: Component) -> Component {
+  if is_one(&left) {
+    right
+  } else if is_one(&right) {
+    left
+  } else {
+    create_binary(Multiply, left, right)
+  }
+}
+
+// Splits a component into a numerator and denominator with all nested fractions combined
+pub(crate) fn fraction(component: &Component) -> (Component, Component) {
+  match component {
+    Component::Function { operator, values } if values.len() == 2 => match operator {
+      Add | Subtract => {
+        let (a, b) = fraction(&values[0]);
+        let (c, d) = fraction(&values[1]);
+
+        // a / b + c / b, becomes (a + c) / b
+        if b.to_string() == d.to_string() {
+          (create_binary(operator.clone(), a, c), b)
+        } else {
+          (
+            create_binary(
+              operator.clone(),
+              product(a, d.clone()),
+              product(c, b.clone()),
+            ),
+            product(b, d),
+          )
+        }
+      }
+
+      Multiply => {
+        let (a, b) = fraction(&values[0]);
+        let (c, d) = fraction(&values[1]);
+        (product(a, c), product(b, d))
+      }
+
+      Divide => {
+        let (a, b) = fraction(&values[0]);
+        let (c, d) = fraction(&values[1]);
+        (product(a, d), product(b, c))
+      }
+
+      // (a / b) ^ n, becomes a ^ n / b ^ n for integer powers
+      Exponent | Pow => match values[1] {
+        Component::Number(f) if f.fract() == 0.0 => {
+          let (a, b) = fraction(&values[0]);
+          let power = |c: Component, n: Prec| {
+            if n == 1.0 || is_one(&c) {
+              c
+            } else {
+              create_binary(Exponent, c, Component::Number(n))
+            }
+          };
+
+          if f >= 0.0 {
+            (power(a, f), power(b, f))
+          } else {
+            (power(b, -f), power(a, -f))
+          }
+        }
+        _ => (combine_values(operator, values), Component::Number(1.0)),
+      },
+
+      _ => (combine_values(operator, values), Component::Number(1.0)),
+    },
+    Component::Function { operator, values } => {
+      (combine_values(operator, values), Component::Number(1.0))
+    }
+    _ => (component.clone(), Component::Number(1.0)),
+  }
+}
+
+// Applies together to all values of a function that is not a fraction itself
+fn combine_values(operator: &Operator, values: &[Component]) -> Component {
+  Component::Function {
+    operator: operator.clone(),
+    values: values.iter().map(together).collect(),
+  }
+}
+
+fn to_division(numerator: Component, denominator: Component) -> Component {
+  if is_one(&denominator) {
+    numerator
+  } else {
+    create_binary(Divide, numerator, denominator)
+  }
+}
+
+/// Combines a sum of fractions into a single fraction
+pub fn together(component: &Component) -> Component {
+  let (numerator, denominator) = fraction(component);
+  to_division(numerator, denominator)
+}
+
+// Converts numerator and denominator to polynomials, returns None if the fraction is not
+// a rational function or contains variables other than the given ones
+fn polynomial_fraction(
+  numerator: &Component,
+  denominator: &Component,
+  vars: &[String],
+) -> Option<(Polynomial, Polynomial)> {
+  let numerator = Polynomial::from_component(numerator)?;
+  let denominator = Polynomial::from_component(denominator)?;
+
+  let mut used = numerator.variables();
+  used.extend(denominator.variables());
+  if denominator.is_zero() || used.iter().any(|v| !vars.contains(v)) {
+    None
+  } else {
+    Some((numerator, denominator))
+  }
+}
+
+/// Combines all fractions and cancels the common polynomial factors of the numerator and denominator
+pub fn cancel(component: &Component) -> Component {
+  let (numerator, denominator) = fraction(component);
+  let vars = create_binary(Divide, numerator.clone(), denominator.clone()).variables();
+
+  match polynomial_fraction(&numerator, &denominator, &vars) {
+    Some((n, d)) => {
+      let gcd = n.gcd(&d);
+      let (n, _) = n.div_rem(&gcd).unwrap();
+      let (d, _) = d.div_rem(&gcd).unwrap();
+
+      // Keep the leading coefficient of the denominator 1, so constant factors end up in the numerator
+      let lead = d.leading_coefficient();
+      to_division(
+        n.scale(1.0 / lead).to_component(),
+        d.scale(1.0 / lead).to_component(),
+      )
+    }
+    None => to_division(numerator, denominator),
+  }
+}
+
+// Solves a square system of linear equations with gaussian elimination
+// Returns None if the system has no unique solution
+fn solve_linear(mut matrix: Vec<Vec<Prec>>, mut rhs: Vec<Prec>) -> Option<Vec<Prec>> {
+  let n = rhs.len();
+
+  for col in 0..n {
+    // Partial pivoting, NaN sorts above every number and is rejected with the singular matrices
+    let pivot = (col..n).max_by(|a, b| matrix[*a][col].abs().total_cmp(&matrix[*b][col].abs()))?;
+    let lead = matrix[pivot][col].abs();
+    if lead.is_nan() || lead < Prec::EPSILON {
+      return None;
+    }
+    matrix.swap(col, pivot);
+    rhs.swap(col, pivot);
+
+    let pivot_row = matrix[col].clone();
+    for row in (col + 1)..n {
+      let f = matrix[row][col] / pivot_row[col];
+      for (value, p) in matrix[row].iter_mut().zip(&pivot_row).skip(col) {
+        *value -= f * p;
+      }
+      rhs[row] -= f * rhs[col];
+    }
+  }
+
+  let mut solution = vec![0.0; n];
+  for row in (0..n).rev() {
+    let sum: Prec = ((row + 1)..n).map(|k| matrix[row][k] * solution[k]).sum();
+    solution[row] = (rhs[row] - sum) / matrix[row][row];
+  }
+  Some(solution)
+}
+
+// Coefficients of a polynomial in a single variable, starting at var ^ 0
+fn dense(p: &Polynomial, var: &str) -> Vec<Prec> {
+  p.coefficients_in(var)
+    .iter()
+    .map(|c| c.evaluate(vec![]).unwrap_or(0.0))
+    .collect()
+}
+
+/// Decomposes a rational function of var into partial fractions
+///
+/// The denominator is factored into real linear and irreducible quadratic factors.
+/// Returns the combined fraction if the component is not a rational function of var.
+pub fn apart(component: &Component, var: &str) -> Component {
+  let (numerator, denominator) = fraction(component);
+  let (n, d) = match polynomial_fraction(&numerator, &denominator, &[var.to_string()]) {
+    Some(fraction) => fraction,
+    None => return to_division(numerator, denominator),
+  };
+
+  let (quotient, remainder) = n.div_rem(&d).unwrap();
+  let degree = d.degree_in(var) as usize;
+  let x = Polynomial::variable(var);
+
+  // Every term is (A * x + B) / factor ^ power, A is left out for linear factors
+  let mut factors = vec![];
+  let mut basis = vec![];
+  for (re, im, multiplicity) in polynomial::factor_roots(&dense(&d, var)) {
+    let (factor, quadratic) = if im == 0.0 {
+      (&x - &Polynomial::constant(re), false)
+    } else {
+      (
+        &(&x.pow(2) - &x.scale(2.0 * re)) + &Polynomial::constant(re * re + im * im),
+        true,
+      )
+    };
+
+    for power in 1..=multiplicity {
+      let (cofactor, _) = d.div_rem(&factor.pow(power as u32)).unwrap();
+      if quadratic {
+        basis.push(dense(&(&cofactor * &x), var));
+      }
+      basis.push(dense(&cofactor, var));
+    }
+    factors.push((factor, quadratic, multiplicity));
+  }
+
+  // Factoring failed to find all roots
+  if degree == 0 || basis.len() != degree {
+    return to_division(numerator, denominator);
+  }
+
+  // Equate the coefficients of the remainder to the sum of the numerators times their cofactors
+  let remainder = dense(&remainder, var);
+  let matrix = (0..degree)
+    .map(|row| {
+      basis
+        .iter()
+        .map(|b| b.get(row).copied().unwrap_or(0.0))
+        .collect()
+    })
+    .collect();
+  let rhs = (0..degree)
+    .map(|row| remainder.get(row).copied().unwrap_or(0.0))
+    .collect();
+
+  let coefficients = match solve_linear(matrix, rhs) {
+    Some(coefficients) => coefficients,
+    None => return to_division(numerator, denominator),
+  };
+
+  let mut sum = if quotient.is_zero() {
+    None
+  } else {
+    Some(quotient.to_component())
+  };
+  let mut coefficients = coefficients.into_iter();
+
+  for (factor, quadratic, multiplicity) in factors {
+    let base = factor.to_component();
+
+    for power in 1..=multiplicity {
+      let numerator = if quadratic {
+        let a = coefficients.next().unwrap();
+        &x.scale(a) + &Polynomial::constant(coefficients.next().unwrap())
+      } else {
+        Polynomial::constant(coefficients.next().unwrap())
+      };
+      if numerator.is_zero() {
+        continue;
+      }
+
+      let denominator = if power == 1 {
+        base.clone()
+      } else {
+        create_binary(Exponent, base.clone(), Component::Number(power as Prec))
+      };
+
+      // A negative leading coefficient is moved into a subtraction
+      sum = Some(match sum {
+        Some(sum) if numerator.leading_coefficient() < 0.0 => create_binary(
+          Subtract,
+          sum,
+          create_binary(Divide, (-numerator).to_component(), denominator),
+        ),
+        Some(sum) => create_binary(
+          Add,
+          sum,
+          create_binary(Divide, numerator.to_component(), denominator),
+        ),
+        None => create_binary(Divide, numerator.to_component(), denominator),
+      });
+    }
+  }
+
+  sum.unwrap_or(Component::Number(0.0))
+}
@@ -0,0 +1,221 @@
+// Serde support, enabled with the serde feature
+// The json schema is documented on Component
+
+use std::fmt;
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::component::{Component, Prec};
+use super::operators::Operator;
+
+impl Serialize for Operator {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&self.to_string())
+  }
+}
+
+impl<'de> Deserialize<'de> for Operator {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    match Operator::from(name.as_str()) {
+      Operator::Error => Err(de::Error::custom(format!("unknown operator {}", name))),
+      operator => Ok(operator),
+    }
+  }
+}
+
+impl Serialize for Component {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self {
+      Component::Variable(c) => serializer.serialize_str(c),
+      // Json has no NaN or infinity, they are tagged so they don't read back as variables
+      Component::Number(f) if !f.is_finite() => {
+        let mut number = serializer.serialize_struct("Number", 1)?;
+        number.serialize_field(
+          "num",
+          if f.is_nan() {
+            "NaN"
+          } else if *f > 0.0 {
+            "inf"
+          } else {
+            "-inf"
+          },
+        )?;
+        number.end()
+      }
+      Component::Number(f) => serializer.serialize_f32(*f),
+      Component::Function { operator, values } => {
+        let mut function = serializer.serialize_struct("Function", 2)?;
+        function.serialize_field("op", operator)?;
+        function.serialize_field("args", values)?;
+        function.end()
+      }
+      Component::End => serializer.serialize_unit(),
+    }
+  }
+}
+
+// The amount of values a function of the operator takes
+fn arity(operator: &Operator) -> usize {
+  match operator {
+    Operator::Sum | Operator::Product => 4,
+    Operator::Add
+    | Operator::Subtract
+    | Operator::Multiply
+    | Operator::Divide
+    | Operator::Modulo
+    | Operator::Exponent
+    | Operator::Pow
+    | Operator::Log
+    | Operator::Root => 2,
+    _ => 1,
+  }
+}
+
+struct ComponentVisitor;
+
+impl<'de> Visitor<'de> for ComponentVisitor {
+  type Value = Component;
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("a number, a variable name, an {\"op\", \"args\"} or {\"num\"} object or null")
+  }
+
+  fn visit_f64<E: de::Error>(self, f: f64) -> Result<Component, E> {
+    Ok(Component::Number(f as Prec))
+  }
+
+  fn visit_i64<E: de::Error>(self, i: i64) -> Result<Component, E> {
+    Ok(Component::Number(i as Prec))
+  }
+
+  fn visit_u64<E: de::Error>(self, u: u64) -> Result<Component, E> {
+    Ok(Component::Number(u as Prec))
+  }
+
+  fn visit_str<E: de::Error>(self, name: &str) -> Result<Component, E> {
+    Ok(Component::Variable(name.to_string()))
+  }
+
+  fn visit_unit<E: de::Error>(self) -> Result<Component, E> {
+    Ok(Component::End)
+  }
+
+  fn visit_none<E: de::Error>(self) -> Result<Component, E> {
+    Ok(Component::End)
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Component, A::Error> {
+    const FIELDS: &[&str] = &["op", "args", "num"];
+    let (mut operator, mut values, mut number) = (None, None, None);
+    while let Some(key) = map.next_key::<String>()? {
+      match key.as_str() {
+        "op" => operator = Some(map.next_value::<Operator>()?),
+        "args" => values = Some(map.next_value::<Vec<Component>>()?),
+        "num" => number = Some(map.next_value::<String>()?),
+        _ => return Err(de::Error::unknown_field(&key, FIELDS)),
+      }
+    }
+
+    if let Some(number) = number {
+      if operator.is_some() || values.is_some() {
+        return Err(de::Error::custom("num can't be combined with op or args"));
+      }
+      return match number.as_str() {
+        "NaN" => Ok(Component::Number(Prec::NAN)),
+        "inf" => Ok(Component::Number(Prec::INFINITY)),
+        "-inf" => Ok(Component::Number(Prec::NEG_INFINITY)),
+        _ => Err(de::Error::invalid_value(
+          de::Unexpected::Str(&number),
+          &"\"NaN\", \"inf\" or \"-inf\"",
+        )),
+      };
+    }
+
+    let operator = operator.ok_or_else(|| de::Error::missing_field("op"))?;
+    let values = values.ok_or_else(|| de::Error::missing_field("args"))?;
+    let expected = arity(&operator);
+    if values.len() != expected {
+      return Err(de::Error::invalid_length(
+        values.len(),
+        &format!("{} arguments for {}", expected, operator).as_str(),
+      ));
+    }
+    Ok(Component::Function { operator, values })
+  }
+}
+
+impl<'de> Deserialize<'de> for Component {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    deserializer.deserialize_any(ComponentVisitor)
+  }
+}
+
+/// Serializes components and equations as their infix string, like `"x ^ 2 + sin(y)"`
+///
+/// # Examples
+/// ```
+/// use calculi::{Component, Equation};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Model {
+///   #[serde(with = "calculi::infix")]
+///   formula: Equation,
+///   #[serde(with = "calculi::infix")]
+///   derivative: Component,
+/// }
+///
+/// let formula = Equation::new("x ^ 3");
+/// let derivative = formula.derive().expression;
+/// let json = serde_json::to_string(&Model { formula, derivative }).unwrap();
+///
+/// assert_eq!(json, r#"{"formula":"x ^ 3","derivative":"3 * x ^ 2"}"#);
+///
+/// let model: Model = serde_json::from_str(&json).unwrap();
+///
+/// assert_eq!(model.derivative.to_string(), "3 * x ^ 2");
+/// ```
+pub mod infix {
+  use serde::{Deserialize, Deserializer, Serializer};
+
+  use super::super::component::Component;
+  use super::super::equation::Equation;
+  use super::super::parser;
+
+  /// Values which can be written as and read from an infix string
+  pub trait Infix: Sized {
+    fn to_infix(&self) -> String;
+    fn from_infix(text: String) -> Self;
+  }
+
+  impl Infix for Component {
+    fn to_infix(&self) -> String {
+      self.to_string()
+    }
+
+    fn from_infix(text: String) -> Self {
+      parser::parse(&text)
+    }
+  }
+
+  impl Infix for Equation {
+    fn to_infix(&self) -> String {
+      self.text.clone()
+    }
+
+    fn from_infix(text: String) -> Self {
+      Equation::new(text)
+    }
+  }
+
+  pub fn serialize<T: Infix, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_infix())
+  }
+
+  pub fn deserialize<'de, T: Infix, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    String::deserialize(deserializer).map(T::from_infix)
+  }
+}
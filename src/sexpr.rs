@@ -0,0 +1,127 @@
+use super::component::{Component, Prec};
+use super::operators::{Operator, Operator::*};
+use super::utils::create_binary;
+
+/// Converts a component to an s-expression, every function is a list with the operator first, like (+ x (* 2 y))
+pub fn to_sexpr(component: &Component) -> String {
+  match component {
+    Component::Variable(c) => c.to_string(),
+    Component::Number(f) => f.to_string(),
+    Component::Function { operator, values } => {
+      let mut parts = vec![operator.to_string()];
+      parts.extend(values.iter().map(to_sexpr));
+      format!("({})", parts.join(" "))
+    }
+    Component::End => String::from("()"),
+  }
+}
+
+// A parsed s-expression, before the operators are checked
+enum Sexpr {
+  Atom(String),
+  List(Vec<Sexpr>),
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+  text
+    .replace('(', " ( ")
+    .replace(')', " ) ")
+    .split_whitespace()
+    .map(String::from)
+    .collect()
+}
+
+fn read(tokens: &mut std::slice::Iter<String>) -> Option<Sexpr> {
+  match tokens.next()?.as_str() {
+    "(" => {
+      let mut items = vec![];
+      loop {
+        match tokens.as_slice().first()?.as_str() {
+          ")" => {
+            tokens.next();
+            return Some(Sexpr::List(items));
+          }
+          _ => items.push(read(tokens)?),
+        }
+      }
+    }
+    ")" => None,
+    atom => Some(Sexpr::Atom(atom.to_string())),
+  }
+}
+
+fn atom(text: &str) -> Option<Component> {
+  let first = text.chars().next()?;
+  // Names starting with a letter are variables, except the names of infinity and NaN which numbers are written as
+  if matches!(first, '0'..='9' | '-' | '+' | '.') || text == "inf" || text == "NaN" {
+    text.parse::<Prec>().ok().map(Component::Number)
+  } else if first.is_alphabetic() && text.chars().all(|c| c.is_alphanumeric() || c == '_') {
+    Some(Component::Variable(text.to_string()))
+  } else {
+    None
+  }
+}
+
+// The amount of values an operator takes, None if it takes any amount
+fn arity(operator: &Operator) -> Option<usize> {
+  match operator {
+    Add | Subtract | Multiply | Divide => None,
+    Modulo | Exponent | Pow | Log | Root => Some(2),
+    Sum | Product => Some(4),
+    _ => Some(1),
+  }
+}
+
+fn component(sexpr: Sexpr) -> Option<Component> {
+  let mut items = match sexpr {
+    Sexpr::Atom(text) => return atom(&text),
+    Sexpr::List(items) if items.is_empty() => return Some(Component::End),
+    Sexpr::List(items) => items.into_iter(),
+  };
+  let operator = match items.next()? {
+    Sexpr::Atom(name) => Operator::from(name.as_str()),
+    Sexpr::List(_) => return None,
+  };
+  let values = items.map(component).collect::<Option<Vec<_>>>()?;
+
+  match (arity(&operator), &operator, values.len()) {
+    (Some(arity), _, len) if arity == len && !operator.compare(&Error) => {
+      Some(Component::Function { operator, values })
+    }
+    (Some(_), _, _) => None,
+    // Variadic operators are applied from left to right, (- a b c) is (a - b) - c
+    // Without values + and * give their identity, with one value - negates and / takes the reciprocal
+    (None, Add, 0) => Some(Component::Number(0.0)),
+    (None, Multiply, 0) => Some(Component::Number(1.0)),
+    (None, _, 0) => None,
+    (None, Subtract, 1) => Some(match values.into_iter().next()? {
+      Component::Number(f) => Component::Number(-f),
+      value => create_binary(Multiply, Component::Number(-1.0), value),
+    }),
+    (None, Divide, 1) => Some(create_binary(
+      Divide,
+      Component::Number(1.0),
+      values.into_iter().next()?,
+    )),
+    (None, _, _) => {
+      let mut values = values.into_iter();
+      let first = values.next()?;
+      Some(values.fold(first, |result, value| {
+        create_binary(operator.clone(), result, value)
+      }))
+    }
+  }
+}
+
+/// Parses an s-expression like (+ x (* 2 y)), the operators are written as in equations
+/// +, -, * and / take any amount of values, the other operators take as many values as in equations
+/// Returns None if the s-expression is invalid
+pub fn from_sexpr(text: &str) -> Option<Component> {
+  let tokens = tokenize(text);
+  let mut tokens = tokens.iter();
+  let sexpr = read(&mut tokens)?;
+  if tokens.next().is_some() {
+    return None;
+  }
+  component(sexpr)
+}
@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+
+use super::component::{Component, Prec};
+use super::equation::Equation;
+use super::operators::{Operator, Operator::*};
+use super::polynomial::{Polynomial, Rational};
+use super::utils::*;
+
+// Maximum amount of terms which are added or multiplied one by one
+const MAX_TERMS: usize = 1_000_000;
+
+// Maximum amount of terms which are written out, every term deepens the component
+const MAX_EXPANDED: usize = 1000;
+
+// Highest power of k which is summed with Faulhaber's formula
+const MAX_POWER: usize = 40;
+
+// Above this not every integer is representable, so the terms can't be counted one by one
+const MAX_EXACT: Prec = 16_777_216.0;
+
+// Amount of terms from a to b, None when the bounds aren't exactly representable integers
+fn count(a: Prec, b: Prec) -> Option<usize> {
+  if a.fract() != 0.0 || b.fract() != 0.0 || a.abs() > MAX_EXACT || b.abs() > MAX_EXACT {
+    return None;
+  }
+  Some(if b < a { 0 } else { (b - a) as usize + 1 })
+}
+
+fn binomial(n: usize, k: usize) -> Rational {
+  (0..k).fold(Rational::new(1, 1), |c, i| {
+    c * Rational::new((n - i) as i64, 1) / Rational::new(i as i64 + 1, 1)
+  })
+}
+
+// Bernoulli numbers, with B1 = 1 / 2 so the sums include their upper bound
+fn bernoulli(count: usize) -> Vec<Rational> {
+  let mut numbers: Vec<Rational> = vec![];
+  for m in 0..count {
+    let sum = (0..m).fold(Rational::new(0, 1), |sum, k| {
+      sum + binomial(m, k) * numbers[k] / Rational::new((m - k + 1) as i64, 1)
+    });
+    numbers.push(Rational::new(1, 1) - sum);
+  }
+  numbers
+}
+
+// Faulhaber's formula, 1 ^ p + 2 ^ p + ... + n ^ p for the polynomial n
+fn power_sum(p: usize, n: &Polynomial<Rational>) -> Polynomial<Rational> {
+  let bernoulli = bernoulli(p + 1);
+  let mut result = Polynomial::zero();
+  for (j, b) in bernoulli.into_iter().enumerate() {
+    let c = binomial(p + 1, j) * b / Rational::new(p as i64 + 1, 1);
+    result = result + n.pow((p + 1 - j) as u32).scale(c);
+  }
+  result
+}
+
+// Sum of a polynomial in k from a to b, the bounds have to be polynomials as well
+// Returns None when the power of k is too high or the rational coefficients overflow, the terms are added one by one then
+fn polynomial_sum(body: &Component, k: &str, a: &Component, b: &Component) -> Option<Component> {
+  let body = Polynomial::<Rational>::from_component(body)?;
+  let a = Polynomial::<Rational>::from_component(a)?;
+  let b = Polynomial::<Rational>::from_component(b)?;
+  let before = &a - &Polynomial::constant(Rational::new(1, 1));
+
+  let coefficients = body.coefficients_in(k);
+  if coefficients.len() > MAX_POWER + 1 {
+    return None;
+  }
+
+  let mut result = Polynomial::zero();
+  for (p, coefficient) in coefficients.iter().enumerate() {
+    let sum = &power_sum(p, &b) - &power_sum(p, &before);
+    result = result + coefficient * &sum;
+  }
+  if result.is_finite() {
+    Some(result.to_component())
+  } else {
+    None
+  }
+}
+
+// Sum of c * r ^ (m * k + d) from a to b, which equals c * r ^ d * (r ^ m) ^ k
+fn geometric_sum(body: &Component, k: &str, a: &Component, b: &Component) -> Option<Component> {
+  let (factor, power) = match body {
+    Component::Function {
+      operator: Multiply,
+      values,
+    } if !depends_on(&values[0], k) => (values[0].clone(), &values[1]),
+    _ => (Component::Number(1.0), body),
+  };
+
+  let (base, exponent) = match power {
+    Component::Function {
+      operator: Exponent,
+      values,
+    }
+    | Component::Function {
+      operator: Pow,
+      values,
+    } if !depends_on(&values[0], k) => (&values[0], &values[1]),
+    _ => return None,
+  };
+
+  // The exponent has to be linear in k
+  let exponent = Polynomial::<Prec>::from_component(exponent)?;
+  let coefficients = exponent.coefficients_in(k);
+  if coefficients.len() != 2 {
+    return None;
+  }
+  let (d, m) = (
+    coefficients[0].to_component(),
+    coefficients[1].to_component(),
+  );
+
+  // c * r ^ d * (q ^ (b + 1) - q ^ a) / (q - 1) with q = r ^ m
+  let ratio = clean(&create_binary(Exponent, base.clone(), m));
+  if ratio.to_float() == Some(1.0) {
+    return None;
+  }
+  let power = |n: Component| create_binary(Exponent, ratio.clone(), n);
+  let sum = create_binary(
+    Divide,
+    create_binary(
+      Subtract,
+      power(create_binary(Add, b.clone(), Component::Number(1.0))),
+      power(a.clone()),
+    ),
+    create_binary(Subtract, ratio.clone(), Component::Number(1.0)),
+  );
+  let factor = create_binary(Multiply, factor, create_binary(Exponent, base.clone(), d));
+  Some(clean(&create_binary(Multiply, factor, sum)))
+}
+
+// Finds a closed form for a sum or product without adding the terms
+fn closed_form(
+  operator: &Operator,
+  body: &Component,
+  k: &str,
+  a: &Component,
+  b: &Component,
+) -> Option<Component> {
+  // The amount of terms, b - a + 1
+  let count = match (
+    Polynomial::<Rational>::from_component(a),
+    Polynomial::<Rational>::from_component(b),
+  ) {
+    (Some(a), Some(b)) => (&(&b - &a) + &Polynomial::constant(Rational::new(1, 1))).to_component(),
+    _ => create_binary(
+      Add,
+      create_binary(Subtract, b.clone(), a.clone()),
+      Component::Number(1.0),
+    ),
+  };
+
+  match operator {
+    // Constant terms
+    Sum if !depends_on(body, k) => Some(clean(&create_binary(Multiply, count, body.clone()))),
+    Product if !depends_on(body, k) => Some(clean(&create_binary(Exponent, body.clone(), count))),
+
+    Sum => polynomial_sum(body, k, a, b).or_else(|| geometric_sum(body, k, a, b)),
+
+    // The product of r ^ f(k) equals r ^ (the sum of f(k))
+    Product => match body {
+      Component::Function {
+        operator: Exponent,
+        values,
+      } if !depends_on(&values[0], k) => {
+        let exponent = closed_form(&Sum, &values[1], k, a, b)?;
+        Some(clean(&create_binary(Exponent, values[0].clone(), exponent)))
+      }
+      _ => None,
+    },
+
+    _ => None,
+  }
+}
+
+// Writes out every term of the sum or product, the bounds have to be integers
+fn expand(operator: &Operator, body: &Component, k: &str, a: Prec, count: usize) -> Component {
+  let combine = if operator.compare(&Sum) {
+    Add
+  } else {
+    Multiply
+  };
+  let k = Component::Variable(k.to_string());
+  let mut result: Option<Component> = None;
+  for j in 0..count {
+    let term = body.substitute(&k, &Component::Number(a + j as Prec));
+    result = Some(match result {
+      Some(result) => create_binary(combine.clone(), result, term),
+      None => term,
+    });
+  }
+  result.unwrap_or(Component::Number(if operator.compare(&Sum) {
+    0.0
+  } else {
+    1.0
+  }))
+}
+
+/// Solves a sum or product with the given variables, values are the expression, the bound variable and the bounds
+/// Concrete bounds are evaluated term by term, otherwise a closed form is used if one is known
+pub fn solve(vars: &HashMap<&str, Prec>, operator: &Operator, values: &[Component]) -> Component {
+  let k = match &values[1] {
+    Component::Variable(k) => k.as_str(),
+    _ => return Component::End,
+  };
+
+  // The bound variable shadows a variable with the same name
+  let mut inner = vars.clone();
+  inner.remove(k);
+  let body = Equation::solve_component(&inner, &values[0]);
+  let a = Equation::solve_component(vars, &values[2]);
+  let b = Equation::solve_component(vars, &values[3]);
+
+  let unsolved = || Component::Function {
+    operator: operator.clone(),
+    values: vec![body.clone(), values[1].clone(), a.clone(), b.clone()],
+  };
+
+  match (a.to_float(), b.to_float()) {
+    (Some(from), Some(to)) => {
+      let count = match count(from, to) {
+        Some(count) if count <= MAX_TERMS => count,
+        _ => return closed_form(operator, &body, k, &a, &b).unwrap_or_else(unsolved),
+      };
+
+      // Add or multiply the terms one by one when they are numbers
+      let mut result = if operator.compare(&Sum) { 0.0 } else { 1.0 };
+      for j in 0..count {
+        inner.insert(k, from + j as Prec);
+        match Equation::solve_component(&inner, &body).to_float() {
+          Some(f) if operator.compare(&Sum) => result += f,
+          Some(f) => result *= f,
+          None => {
+            return closed_form(operator, &body, k, &a, &b).unwrap_or_else(|| {
+              if count <= MAX_EXPANDED {
+                clean(&Equation::solve_component(
+                  vars,
+                  &expand(operator, &body, k, from, count),
+                ))
+              } else {
+                unsolved()
+              }
+            })
+          }
+        }
+      }
+      Component::Number(result)
+    }
+    _ => closed_form(operator, &body, k, &a, &b).unwrap_or_else(unsolved),
+  }
+}
+
+/// Replaces every sum and product with concrete bounds by its terms, so it can be evaluated without the bound variable
+/// Returns None if a sum or product has bounds which are not integers
+pub fn unroll(component: &Component) -> Option<Component> {
+  match component {
+    Component::Function { operator, values } => {
+      let values = values.iter().map(unroll).collect::<Option<Vec<_>>>()?;
+      match (operator, component.bound_variable()) {
+        (Sum, Some(k)) | (Product, Some(k)) => {
+          let a = values[2].to_float()?;
+          let count = count(a, values[3].to_float()?).filter(|&count| count <= MAX_EXPANDED)?;
+          Some(expand(operator, &values[0], k, a, count))
+        }
+        _ => Some(Component::Function {
+          operator: operator.clone(),
+          values,
+        }),
+      }
+    }
+    _ => Some(component.clone()),
+  }
+}
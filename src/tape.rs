@@ -0,0 +1,150 @@
+use super::component::{Component, Prec};
+use super::operators::Operator;
+use super::summation;
+
+#[derive(Debug, Clone)]
+enum Node {
+  Number(Prec),
+  Variable(usize),
+  // Operator with the indices of its values on the tape, which are always lower than its own index
+  Function {
+    operator: Operator,
+    values: Vec<usize>,
+    // Whether the node depends on a variable, constant nodes need no derivatives
+    variable: bool,
+  },
+}
+
+/// A component recorded as a flat list of operations, for reverse mode automatic differentiation
+/// The full gradient is found with one forward and one backward pass over the tape,
+/// regardless of the amount of variables
+///
+/// # Examples
+/// ```
+/// let tape = calculi::Equation::new("x * y + sin(z)").tape(&["x", "y", "z"]).unwrap();
+///
+/// let (value, gradient) = tape.gradient(&[2.0, 3.0, 0.0]).unwrap();
+///
+/// assert_eq!(value, 6.0);
+/// assert_eq!(gradient, vec![3.0, 2.0, 1.0]);
+/// assert_eq!(tape.value(&[2.0, 3.0, 0.0]), Some(6.0));
+/// assert_eq!(tape.gradient(&[2.0, 3.0]), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tape {
+  nodes: Vec<Node>,
+  variables: usize,
+}
+
+impl Tape {
+  /// Records component on a tape, the values passed to gradient are in the order of vars
+  /// Returns None if the component contains other variables or invalid parts
+  pub fn new(component: &Component, vars: &[&str]) -> Option<Tape> {
+    let mut tape = Tape {
+      nodes: vec![],
+      variables: vars.len(),
+    };
+    tape.record(&summation::unroll(component)?, vars)?;
+    Some(tape)
+  }
+
+  // Adds component and its values to the tape, returns its index
+  fn record(&mut self, component: &Component, vars: &[&str]) -> Option<usize> {
+    let node = match component {
+      Component::Number(f) => Node::Number(*f),
+      Component::Variable(c) => Node::Variable(vars.iter().position(|v| v == c)?),
+      Component::Function { operator, values } => {
+        let values = values
+          .iter()
+          .map(|x| self.record(x, vars))
+          .collect::<Option<Vec<_>>>()?;
+
+        // Check if the operator accepts the amount of values
+        operator.apply(&vec![1.0; values.len()])?;
+
+        Node::Function {
+          operator: operator.clone(),
+          variable: values.iter().any(|i| self.depends_on_variable(*i)),
+          values,
+        }
+      }
+      Component::End => return None,
+    };
+
+    self.nodes.push(node);
+    Some(self.nodes.len() - 1)
+  }
+
+  // Forward pass, the results of every node in order, every node only refers to nodes before it
+  fn forward(&self, values: &[Prec]) -> Option<Vec<Prec>> {
+    if values.len() != self.variables {
+      return None;
+    }
+
+    let mut results = Vec::with_capacity(self.nodes.len());
+    for node in &self.nodes {
+      results.push(match node {
+        Node::Number(f) => *f,
+        Node::Variable(i) => values[*i],
+        Node::Function {
+          operator, values, ..
+        } => {
+          let values: Vec<_> = values.iter().map(|i| results[*i]).collect();
+          operator.apply(&values)?
+        }
+      });
+    }
+    Some(results)
+  }
+
+  /// Evaluates the recorded component at values without the backward pass
+  /// Returns None if the amount of values does not match the amount of variables
+  pub fn value(&self, values: &[Prec]) -> Option<Prec> {
+    Some(self.forward(values)?.last().copied().unwrap_or(0.0))
+  }
+
+  /// Evaluates the recorded component at values, in the order of the variables given to new
+  /// Returns the value and the partial derivatives with respect to every variable
+  /// Returns None if the amount of values does not match the amount of variables
+  pub fn gradient(&self, values: &[Prec]) -> Option<(Prec, Vec<Prec>)> {
+    let results = self.forward(values)?;
+
+    // Backward pass, the adjoint of a node is the derivative of the output with respect to the node
+    let mut adjoints = vec![0.0; self.nodes.len()];
+    let mut gradient = vec![0.0; self.variables];
+    if let Some(last) = adjoints.last_mut() {
+      *last = 1.0;
+    }
+
+    for (index, node) in self.nodes.iter().enumerate().rev() {
+      let adjoint = adjoints[index];
+      match node {
+        Node::Variable(i) => gradient[*i] += adjoint,
+        Node::Function {
+          operator,
+          values,
+          variable: true,
+        } if adjoint != 0.0 => {
+          let arguments: Vec<_> = values.iter().map(|i| results[*i]).collect();
+          let partials = operator.partials(&arguments)?;
+          for (i, partial) in values.iter().zip(partials) {
+            if self.depends_on_variable(*i) {
+              adjoints[*i] += adjoint * partial;
+            }
+          }
+        }
+        _ => (),
+      }
+    }
+
+    Some((results.last().copied().unwrap_or(0.0), gradient))
+  }
+
+  fn depends_on_variable(&self, index: usize) -> bool {
+    match &self.nodes[index] {
+      Node::Number(_) => false,
+      Node::Variable(_) => true,
+      Node::Function { variable, .. } => *variable,
+    }
+  }
+}
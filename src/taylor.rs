@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use super::component::{Component, Prec};
+use super::derive;
+use super::derive::Derivatives;
+use super::equation::Equation;
+use super::interval;
+use super::operators::Operator::*;
+use super::polynomial::{snap, Coefficient, Polynomial, Rational};
+use super::rational;
+use super::utils::*;
+
+// Coefficients of p(about + h) in h, starting at h ^ 0
+fn shifted(p: &Component, var: &str, about: Rational) -> Option<Vec<Rational>> {
+  let p = Polynomial::<Rational>::from_component(p)?;
+  if p.variables().iter().any(|v| v != var) {
+    return None;
+  }
+
+  // Horner's method with var + about in place of var
+  let shift = Polynomial::variable(var) + Polynomial::constant(about);
+  let mut result = Polynomial::zero();
+  for c in p.coefficients_in(var).iter().rev() {
+    result = &(&result * &shift) + c;
+  }
+  if !result.is_finite() {
+    return None;
+  }
+  Some(
+    result
+      .coefficients_in(var)
+      .iter()
+      .map(|c| c.leading_coefficient())
+      .collect(),
+  )
+}
+
+// Exact coefficients of a rational function of var, found by dividing the power series of the
+// numerator and denominator around about
+// Returns None if the component is not a rational function, or the fractions overflow
+fn rational_coefficients(
+  component: &Component,
+  var: &str,
+  about: Prec,
+  order: usize,
+) -> Option<Vec<Prec>> {
+  let (numerator, denominator) = rational::fraction(component);
+  let about = Rational::from_float(about)?;
+  let p = shifted(&numerator, var, about)?;
+  let q = shifted(&denominator, var, about)?;
+  if q[0].is_zero() {
+    return None;
+  }
+
+  let get = |c: &[Rational], i: usize| c.get(i).cloned().unwrap_or_else(Rational::zero);
+  let mut coefficients: Vec<Rational> = vec![];
+  for k in 0..=order {
+    let mut c = get(&p, k);
+    for j in 1..=k {
+      c = c.checked_sub(get(&q, j).checked_mul(coefficients[k - j])?)?;
+    }
+    coefficients.push(c.checked_div(q[0])?);
+  }
+  Some(coefficients.iter().map(Rational::to_float).collect())
+}
+
+// Coefficient of a term, the value of the derivative at the point divided by the factorial
+// Stays a component when the derivative contains other variables
+fn coefficient(derived: &Component, var: &str, about: Prec, factorial: Prec) -> Option<Component> {
+  let mut vars = HashMap::new();
+  vars.insert(var, about);
+  let value = derive::simplify(&Equation::solve_component(&vars, derived));
+
+  match value.to_float() {
+    Some(f) if !f.is_finite() => None,
+    Some(f) => Some(Component::Number(snap(f / factorial))),
+    None if factorial == 1.0 => Some(value),
+    None => Some(create_binary(Divide, value, Component::Number(factorial))),
+  }
+}
+
+// Creates coefficient * (var - about) ^ power, the sign of a numeric coefficient is returned separately
+fn term(coefficient: Component, var: &str, about: Prec, power: usize) -> (Component, bool) {
+  let base = if about == 0.0 {
+    Component::Variable(var.to_string())
+  } else {
+    create_binary(
+      if about < 0.0 { Add } else { Subtract },
+      Component::Variable(var.to_string()),
+      Component::Number(about.abs()),
+    )
+  };
+  let power = match power {
+    0 => None,
+    1 => Some(base),
+    _ => Some(create_binary(
+      Exponent,
+      base,
+      Component::Number(power as Prec),
+    )),
+  };
+
+  let (power, f) = match (power, coefficient.to_float()) {
+    (None, Some(f)) => return (Component::Number(f.abs()), f < 0.0),
+    (None, None) => return (coefficient, false),
+    (Some(power), Some(f)) => (power, f),
+    (Some(power), None) => return (create_binary(Multiply, coefficient, power), false),
+  };
+
+  let magnitude = f.abs();
+  let term = match reciprocal_integer(magnitude) {
+    _ if magnitude == 1.0 => power,
+    Some(inverse) => create_binary(Divide, power, Component::Number(inverse)),
+    None => create_binary(Multiply, Component::Number(magnitude), power),
+  };
+  (term, f < 0.0)
+}
+
+/// Creates the taylor polynomial of a component around about, up to and including the given order
+/// Coefficients of rational functions are computed exactly, other functions are derived and evaluated at the point
+/// Returns None if a derivative is not finite at the point
+pub fn taylor(component: &Component, var: &str, about: Prec, order: usize) -> Option<Component> {
+  let coefficients = match rational_coefficients(component, var, about, order) {
+    Some(coefficients) => coefficients.into_iter().map(Component::Number).collect(),
+    None => {
+      let mut derivatives = Derivatives::new(component);
+      let mut factorial = 1.0;
+      (0..=order)
+        .map(|k| {
+          factorial *= k.max(1) as Prec;
+          coefficient(&derivatives.get(&vec![var; k]), var, about, factorial)
+        })
+        .collect::<Option<Vec<_>>>()?
+    }
+  };
+
+  let mut result: Option<Component> = None;
+  for (k, coefficient) in coefficients.into_iter().enumerate() {
+    if coefficient.to_float() == Some(0.0) {
+      continue;
+    }
+
+    let (term, negative) = term(coefficient, var, about, k);
+    result = Some(match result {
+      None if negative => match term.to_float() {
+        Some(f) => Component::Number(-f),
+        None => create_binary(Multiply, Component::Number(-1.0), term),
+      },
+      None => term,
+      Some(prev) => create_binary(if negative { Subtract } else { Add }, prev, term),
+    });
+  }
+
+  Some(result.unwrap_or(Component::Number(0.0)))
+}
+
+/// Bounds the error of the taylor polynomial of the given order at x with the lagrange remainder,
+/// max |f^(order + 1)| * |x - about| ^ (order + 1) / (order + 1)!
+/// The maximum between about and x is bounded with interval arithmetic, so the bound is guaranteed
+/// Returns None if the component contains other variables or the derivative is not bounded between about and x
+pub fn remainder_bound(
+  component: &Component,
+  var: &str,
+  about: Prec,
+  order: usize,
+  x: Prec,
+) -> Option<Prec> {
+  if component.variables().iter().any(|v| v != var) {
+    return None;
+  }
+
+  let next = Derivatives::new(component).get(&vec![var; order + 1]);
+  let maximum = interval::bound(&next, var, about, x)?;
+  let factorial: f64 = (1..=order + 1).map(|k| k as f64).product();
+  let distance = (f64::from(x) - f64::from(about)).abs();
+  Some(interval::round_up(
+    maximum * distance.powi(order as i32 + 1) / factorial,
+  ))
+}
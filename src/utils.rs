@@ -1,5 +1,10 @@
-use super::component::Component;
+use std::collections::HashMap;
+
+use super::component::{Component, Prec};
+use super::equation::Equation;
 use super::operators::Operator;
+use super::parser;
+use super::polynomial::snap;
 
 pub(crate) fn create_unary(operator: Operator, component: Component) -> Component {
   Component::Function {
@@ -13,4 +18,23 @@ pub(crate) fn create_binary(operator: Operator, left: Component, right: Componen
     operator,
     values: vec![left, right],
   }
-}
\ No newline at end of file
+}
+
+pub(crate) fn depends_on(component: &Component, var: &str) -> bool {
+  component.variables().iter().any(|v| v == var)
+}
+
+// The integer n if f is 1 / n, coefficients like 1 / 3 are written as a division to prevent rounding in the text
+pub(crate) fn reciprocal_integer(f: Prec) -> Option<Prec> {
+  let inverse = snap(1.0 / f);
+  if f.fract() != 0.0 && inverse.fract() == 0.0 {
+    Some(inverse)
+  } else {
+    None
+  }
+}
+
+// Folds constants and removes trivial operations
+pub(crate) fn clean(component: &Component) -> Component {
+  parser::simplify(Equation::solve_component(&HashMap::new(), component))
+}